@@ -0,0 +1,55 @@
+use super::renderer::LineIdx;
+use super::utils;
+use super::{Action, Error, Operation, States};
+use crate::bookmarks;
+
+use std::path::Path;
+
+pub struct AddBookmark {
+    pub line_idx: LineIdx,
+    // Defaults to the name of the directory.
+    pub name: Option<String>,
+}
+
+impl Action for AddBookmark {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some(dir) = states
+            .actions
+            .rendered_lines
+            .get(self.line_idx)
+            .and_then(|item| {
+                if item.metadata.is_dir() {
+                    Some(item.path.to_path_buf())
+                } else {
+                    item.path.parent().map(Path::to_path_buf)
+                }
+            })
+            .await
+        else {
+            return Ok(());
+        };
+
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| dir.display().to_string()),
+        };
+        if !bookmarks::is_valid_name(&name) {
+            return Err(Error::InvalidInput(format!(
+                "invalid bookmark name: {name:?}"
+            )));
+        }
+
+        let file = utils::bookmarks_file()?;
+        bookmarks::update(&file, |bookmarks| {
+            bookmarks.insert(name, dir);
+        })
+        .map_err(|e| Error::io(Operation::SaveState, &file, e))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::file_ops::{self, FileOp};
+use super::journal::{Direction, Mutation};
+use super::refresh::refresh_trees;
+use super::rename_plan::{self, Rename};
+use super::{Action, DirArg, Error, Operation, States};
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+pub struct BulkRename {
+    pub nvim: Neovim<NvimWtr>,
+    pub dir: DirArg,
+    pub names: Vec<(String, String)>,
+}
+
+impl Action for BulkRename {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let dir = self.dir.as_path();
+
+        let mut renames = Vec::with_capacity(self.names.len());
+        for (from, to) in &self.names {
+            let to = to.trim_end_matches('/');
+            if to.is_empty() {
+                return Err(Error::InvalidInput(format!(
+                    "no new name is given for {from}"
+                )));
+            }
+
+            renames.push(Rename {
+                from: dir.join(from),
+                to: dir.join(to),
+            });
+        }
+
+        let ops: Vec<FileOp> = renames
+            .iter()
+            .filter(|r| r.from != r.to)
+            .map(|r| FileOp::rename(&r.from, &r.to))
+            .collect();
+        let dests: BTreeSet<PathBuf> = renames.iter().map(|r| r.to.clone()).collect();
+        let steps = rename_plan::plan(renames)?;
+
+        file_ops::will(&self.nvim, &ops).await?;
+
+        let mut done: Vec<Rename> = Vec::with_capacity(steps.len());
+        let mut done_ops = Vec::with_capacity(steps.len());
+        let mut res = Ok(());
+
+        for Rename { from, to } in steps {
+            let op = FileOp::rename(&from, &to);
+            if let Err(e) = std::fs::rename(&from, &to) {
+                res = Err(Error::io(Operation::Rename { to }, &from, e));
+                break;
+            }
+            done.push(Rename { from, to });
+            done_ops.push(op);
+        }
+
+        if let Err(e) = res {
+            // The steps done are reverted newest first, so that nothing is left under a
+            // temporary name. Those that cannot be are kept, and where they are is reported.
+            while let Some(step) = done.last() {
+                if std::fs::rename(&step.to, &step.from).is_err() {
+                    break;
+                }
+                done.pop();
+                done_ops.pop();
+            }
+
+            let stranded = stranded(&done, &dests);
+            res = if stranded.is_empty() {
+                Err(e)
+            } else {
+                e.notify(&self.nvim).await?;
+                Err(Error::InvalidInput(format!(
+                    "could not revert the renames, entries are left at {}",
+                    stranded.join(", ")
+                )))
+            };
+        }
+
+        // Steps through temporary names are only reported when the renames stopped halfway.
+        let done_ops = if res.is_ok() { ops } else { done_ops };
+        file_ops::did(&self.nvim, &done_ops).await;
+
+        if !done.is_empty() {
+            let done = done
+                .into_iter()
+                .map(|Rename { from, to }| Mutation::Rename { from, to })
+                .collect();
+            let mutation = Mutation::Batch(done);
+            let dirs: BTreeSet<PathBuf> = mutation.parents();
+
+            states
+                .actions
+                .expanded_dir
+                .lock()
+                .then(|expanded_dir| mutation.move_expanded(expanded_dir, Direction::Redo))
+                .await;
+            states.actions.journal.record(mutation).await;
+            states.actions.rendered_lines.selection().take().await;
+
+            refresh_trees(&self.nvim, states, &dirs).await?;
+        }
+
+        res.map(|()| Value::from(true))
+    }
+}
+
+// Temporary names that entries were moved to and not moved on from.
+fn stranded(done: &[Rename], dests: &BTreeSet<PathBuf>) -> Vec<String> {
+    done.iter()
+        .enumerate()
+        .filter(|(idx, step)| {
+            !dests.contains(&step.to) && !done[idx + 1..].iter().any(|later| later.from == step.to)
+        })
+        .map(|(_, step)| step.to.display().to_string())
+        .collect()
+}
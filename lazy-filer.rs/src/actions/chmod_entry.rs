@@ -0,0 +1,81 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::refresh;
+use super::renderer::LineIdx;
+use super::{Action, Error, Operation, States};
+use crate::chmod::{self, Mode};
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+pub struct ChmodEntry {
+    pub nvim: Neovim<NvimWtr>,
+    pub line_idx: LineIdx,
+    pub mode: String,
+    pub recursive: bool,
+}
+
+impl Action for ChmodEntry {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some(mode) = Mode::parse(self.mode.trim()) else {
+            let msg = format!("invalid mode: {}", self.mode);
+            return Err(Error::InvalidInput(msg));
+        };
+
+        let lines = &states.actions.rendered_lines;
+
+        let selection = lines.selection().take().await;
+        let from_selection = !selection.is_empty();
+        let paths: Vec<PathBuf> = if from_selection {
+            selection.into_iter().collect()
+        } else {
+            let Some(path) = lines
+                .get(self.line_idx)
+                .and_then(|item| Some(item.path.to_path_buf()))
+                .await
+            else {
+                return Ok(());
+            };
+            vec![path]
+        };
+
+        let mut parents = BTreeSet::new();
+        let mut result = Ok(());
+        for path in paths {
+            let recursive = self.recursive;
+            let mode = mode.clone();
+            let task = {
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || chmod::chmod(&path, &mode, recursive))
+            };
+            let res = match task.await {
+                Ok(res) => res,
+                Err(e) => Err(std::io::Error::other(e)),
+            };
+
+            if let Some(parent) = path.parent() {
+                parents.insert(parent.to_path_buf());
+            }
+            if let Err(e) = res {
+                result = Err(Error::io(Operation::Chmod, &path, e));
+                break;
+            }
+        }
+
+        // Re-reading the parents picks up the new permissions, and so do the expanded
+        // directories below them when the change was recursive.
+        if self.recursive {
+            refresh::refresh_trees(&self.nvim, states, &parents).await?;
+        } else {
+            refresh::refresh_dirs(&self.nvim, states, &parents).await?;
+        }
+        if from_selection {
+            lines.edit(&self.nvim).redraw(|_| ..).await?;
+        }
+
+        result
+    }
+}
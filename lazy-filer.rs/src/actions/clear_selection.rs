@@ -0,0 +1,22 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::{Action, Error, States};
+
+pub struct ClearSelection {
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for ClearSelection {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let lines = &states.actions.rendered_lines;
+
+        lines.selection().take().await;
+
+        lines.edit(&self.nvim).redraw(|_| ..).await?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,11 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 
-use super::renderer::{Items, Level, LineIdx};
+use super::file_ops::{self, FileOp};
+use super::journal::Mutation;
+use super::renderer::LineIdx;
 use super::utils;
-use super::{Action, States};
+use super::{Action, Error, Operation, States};
 use crate::fs::File;
 
 use std::ffi::OsStr;
@@ -20,7 +22,7 @@ pub struct CreateEntry {
 impl Action for CreateEntry {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let fname = safe_fname(&self.fname);
 
         let Some(entry) = states
@@ -29,15 +31,14 @@ impl Action for CreateEntry {
             .get(self.line_idx)
             .and_then(|item| {
                 let dir = if item.metadata.is_dir() {
-                    Some((&*item.path, item.level.increment()))
+                    Some(&*item.path)
                 } else {
-                    item.path.parent().map(|dir| (dir, item.level))
+                    item.path.parent()
                 };
 
-                dir.map(|(dir, level)| Entry {
+                dir.map(|dir| Entry {
                     dir: dir.to_path_buf(),
                     file: dir.join(fname),
-                    level,
                 })
             })
             .await
@@ -45,39 +46,57 @@ impl Action for CreateEntry {
             return Ok(());
         };
 
-        let file = if self.fname.ends_with('/') {
+        let is_dir = self.fname.ends_with('/');
+        let ops = [FileOp::Create {
+            path: entry.file.clone(),
+            dir: is_dir,
+        }];
+        file_ops::will(&self.nvim, &ops).await?;
+
+        let file = if is_dir {
             let perm = 0o775;
             let mut builder = DirBuilder::new();
             builder.mode(perm);
 
-            if builder.create(&entry.file).is_err() {
-                return Ok(());
+            if let Err(e) = builder.create(&entry.file) {
+                return Err(Error::io(Operation::Create, &entry.file, e));
             }
-            File::empty_directory(perm)
+            File::read_from_path(&entry.file).unwrap_or_else(|_| File::empty_directory(perm))
         } else {
             let perm = 0o664;
             let mut opts = OpenOptions::new();
-            opts.write(true).create(true).mode(perm);
-            if opts.open(&entry.file).is_err() {
-                return Ok(());
+            opts.write(true).create_new(true).mode(perm);
+            if let Err(e) = opts.open(&entry.file) {
+                return Err(Error::io(Operation::Create, &entry.file, e));
             }
 
-            File::regular(perm)
+            File::read_from_path(&entry.file).unwrap_or_else(|_| File::regular(perm))
         };
-
-        insert(
-            &self.nvim,
-            &states.actions.rendered_lines,
-            &entry.file,
-            entry.level,
-            &file,
-        )
-        .await?;
+        file_ops::did(&self.nvim, &ops).await;
 
         states.actions.expanded_dir.insert(entry.dir.clone()).await;
 
         let target_dir = utils::get_entries(&states.root_file, &entry.dir).await;
-        target_dir.insert(fname, file).await;
+        target_dir.insert(fname, file.clone()).await;
+
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+        target_dir
+            .render_new_child(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &states.actions.view.get().await,
+                &entry.file,
+                &file,
+            )
+            .await?;
+
+        let mutation = Mutation::Create {
+            path: entry.file,
+            dir: is_dir,
+            trashed: None,
+        };
+        states.actions.journal.record(mutation).await;
 
         Ok(())
     }
@@ -91,26 +110,4 @@ fn safe_fname(s: &str) -> &OsStr {
 struct Entry {
     dir: PathBuf,
     file: PathBuf,
-    level: Level,
-}
-
-async fn insert(
-    nvim: &Neovim<NvimWtr>,
-    lines: &Items,
-    path: &Path,
-    level: Level,
-    file: &File,
-) -> Result<(), NvimErr> {
-    lines
-        .edit(nvim)
-        .insert_dyn(utils::file_to_item(level, path, file), |lines| {
-            lines
-                .iter()
-                .enumerate()
-                .find_map(|(i, item)| if item.path < path { None } else { Some(i) })
-                .unwrap_or(lines.len())
-        })
-        .await?;
-
-    Ok(())
 }
@@ -0,0 +1,159 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::file_ops::{self, FileOp};
+use super::journal::Mutation;
+use super::renderer::LineIdx;
+use super::states::{LinkKind, Yanked};
+use super::utils;
+use super::{Action, Error, Operation, States};
+use crate::fs::File;
+
+use std::ffi::OsStr;
+use std::io::Error as IoErr;
+use std::path::{Component, Path, PathBuf};
+
+// Links the yanked entries into the directory under the cursor line. The register is kept,
+// even for cut entries, as nothing has been moved. A link is named `fname` if given, which
+// needs a single yanked entry, or else after its target.
+pub struct CreateLink {
+    pub line_idx: LineIdx,
+    pub nvim: Neovim<NvimWtr>,
+    pub kind: LinkKind,
+    pub fname: String,
+}
+
+impl Action for CreateLink {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some(Yanked { mode: _, paths }) = states.actions.register.get().await else {
+            return Ok(());
+        };
+
+        let Some(dest_dir) = states
+            .actions
+            .rendered_lines
+            .get(self.line_idx)
+            .and_then(|item| {
+                if item.metadata.is_dir() {
+                    Some(item.path.to_path_buf())
+                } else {
+                    item.path.parent().map(Path::to_path_buf)
+                }
+            })
+            .await
+        else {
+            return Ok(());
+        };
+
+        let fname: &Path = self.fname.as_ref();
+        let fname = fname.file_name();
+        if fname.is_some() && paths.len() > 1 {
+            return Err(Error::InvalidInput(
+                "a link name needs a single yanked entry".to_string(),
+            ));
+        }
+
+        for target in &paths {
+            link(&self.nvim, states, self.kind, target, &dest_dir, fname).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn link(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    kind: LinkKind,
+    target: &Path,
+    dest_dir: &Path,
+    fname: Option<&OsStr>,
+) -> Result<(), Error> {
+    // Without a name, a link takes its target's, or a free one if that is taken, as it always
+    // is next to the target.
+    let fname = match (fname, target.file_name()) {
+        (Some(fname), _) => fname.to_os_string(),
+        (None, Some(fname)) if dest_dir.join(fname).symlink_metadata().is_ok() => {
+            utils::free_name(dest_dir, fname, "link", target.is_dir())
+        }
+        (None, Some(fname)) => fname.to_os_string(),
+        (None, None) => return Ok(()),
+    };
+
+    let dst = dest_dir.join(&fname);
+    let op = Operation::Link { to: dst.clone() };
+
+    if dst.symlink_metadata().is_ok() {
+        return Err(Error::already_exists(op, target));
+    }
+
+    let content = match kind {
+        LinkKind::Symbolic => {
+            relative_target(target, dest_dir).map_err(|e| Error::io(op.clone(), target, e))?
+        }
+        LinkKind::Hard => target.to_path_buf(),
+    };
+
+    let ops = [FileOp::Create {
+        path: dst.clone(),
+        dir: false,
+    }];
+    file_ops::will(nvim, &ops).await?;
+
+    make_link(kind, &content, &dst).map_err(|e| Error::io(op.clone(), target, e))?;
+    let file = File::read_from_path(&dst).map_err(|e| Error::io(op, target, e))?;
+
+    let mutation = Mutation::Link {
+        target: content,
+        path: dst.clone(),
+        kind,
+    };
+    states.actions.journal.record(mutation).await;
+    file_ops::did(nvim, &ops).await;
+
+    let target_dir = utils::get_entries(&states.root_file, dest_dir).await;
+    target_dir.insert(&fname, file.clone()).await;
+
+    let lines = &states.actions.rendered_lines;
+    if states.actions.expanded_dir.contains(dest_dir).await {
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+        let view = states.actions.view.get().await;
+        target_dir
+            .render_new_child(nvim, lines, &expanded_dir, &view, &dst, &file)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub fn make_link(kind: LinkKind, target: &Path, path: &Path) -> Result<(), IoErr> {
+    match kind {
+        LinkKind::Symbolic => std::os::unix::fs::symlink(target, path),
+        LinkKind::Hard => std::fs::hard_link(target, path),
+    }
+}
+
+// Symlinks are made relative, so that they survive moving the tree that contains both ends.
+// Both ends are resolved first, as the filer may reach them through other symlinks. The
+// target itself is not resolved: linking to a symlink points at that symlink.
+fn relative_target(target: &Path, dest_dir: &Path) -> Result<PathBuf, IoErr> {
+    let (Some(parent), Some(fname)) = (target.parent(), target.file_name()) else {
+        return Ok(target.to_path_buf());
+    };
+    let target = parent.canonicalize()?.join(fname);
+    let dest_dir = dest_dir.canonicalize()?;
+
+    let common = target
+        .components()
+        .zip(dest_dir.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let ups = dest_dir
+        .components()
+        .skip(common)
+        .map(|_| Component::ParentDir);
+    Ok(ups.chain(target.components().skip(common)).collect())
+}
@@ -0,0 +1,21 @@
+use super::utils;
+use super::{Action, Error, Operation, States};
+use crate::bookmarks;
+
+pub struct DeleteBookmark {
+    pub name: String,
+}
+
+impl Action for DeleteBookmark {
+    type Resp = ();
+
+    async fn run(&self, _states: &States) -> Result<Self::Resp, Error> {
+        let file = utils::bookmarks_file()?;
+        bookmarks::update(&file, |bookmarks| {
+            bookmarks.remove(&self.name);
+        })
+        .map_err(|e| Error::io(Operation::SaveState, &file, e))?;
+
+        Ok(())
+    }
+}
@@ -1,21 +1,30 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 
+use super::file_ops::{self, FileOp};
+use super::journal::Mutation;
 use super::renderer::LineIdx;
 use super::utils;
-use super::{Action, States};
+use super::{Action, Error, Operation, States};
 
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
 pub struct DeleteEntry {
     pub line_idx: LineIdx,
     pub nvim: Neovim<NvimWtr>,
+    pub trash: bool,
 }
 
 impl Action for DeleteEntry {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let selection = states.actions.rendered_lines.selection().take().await;
+        if !selection.is_empty() {
+            return self.remove_selected(states, selection).await;
+        }
+
         let Some((is_link, entry)) = states
             .actions
             .rendered_lines
@@ -41,9 +50,7 @@ impl Action for DeleteEntry {
         match entry {
             Entry::Recursive { parent, path } => {
                 let target_dir = utils::get_entries(&states.root_file, &parent).await;
-                if target_dir.remove_fs(&path, !is_link).await.is_err() {
-                    return Ok(());
-                }
+                self.remove(states, &target_dir, &path, !is_link).await?;
 
                 states.actions.expanded_dir.remove(&path).await;
 
@@ -56,9 +63,7 @@ impl Action for DeleteEntry {
             }
             Entry::Single { parent, path } => {
                 let target_dir = utils::get_entries(&states.root_file, &parent).await;
-                if target_dir.remove_fs(&path, false).await.is_err() {
-                    return Ok(());
-                }
+                self.remove(states, &target_dir, &path, false).await?;
 
                 states
                     .actions
@@ -73,6 +78,78 @@ impl Action for DeleteEntry {
     }
 }
 
+impl DeleteEntry {
+    async fn remove_selected(
+        &self,
+        states: &States,
+        selection: BTreeSet<PathBuf>,
+    ) -> Result<(), Error> {
+        // Entries under a selected directory go away with it.
+        let mut targets: Vec<PathBuf> = Vec::new();
+        for path in selection {
+            if !targets.iter().any(|target| path.starts_with(target)) {
+                targets.push(path);
+            }
+        }
+
+        for path in targets {
+            let Some(parent) = path.parent() else {
+                continue;
+            };
+            let is_link = path.is_symlink();
+            let recursive = path.is_dir() && !is_link;
+
+            let target_dir = utils::get_entries(&states.root_file, parent).await;
+            self.remove(states, &target_dir, &path, recursive).await?;
+
+            states
+                .actions
+                .expanded_dir
+                .lock()
+                .then(|expanded_dir| expanded_dir.retain(|dir| !dir.starts_with(&path)))
+                .await;
+
+            states
+                .actions
+                .rendered_lines
+                .edit(&self.nvim)
+                .remove_range(|lines| utils::find_in_dir(&path, lines))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn remove(
+        &self,
+        states: &States,
+        target_dir: &utils::Entries<'_>,
+        path: &Path,
+        recursive: bool,
+    ) -> Result<(), Error> {
+        let ops = [FileOp::delete(path)];
+        file_ops::will(&self.nvim, &ops).await?;
+
+        if self.trash {
+            let trashed = match target_dir.trash_fs(path, recursive).await {
+                Ok(trashed) => trashed,
+                Err(e) => return Err(Error::io(Operation::Trash, path, e)),
+            };
+
+            let mutation = Mutation::Trash {
+                path: path.to_path_buf(),
+                trashed,
+            };
+            states.actions.journal.record(mutation).await;
+        } else if let Err(e) = target_dir.remove_fs(path, recursive).await {
+            return Err(Error::io(Operation::Delete, path, e));
+        }
+        file_ops::did(&self.nvim, &ops).await;
+
+        Ok(())
+    }
+}
+
 enum Entry {
     Recursive { parent: PathBuf, path: PathBuf },
     Single { parent: PathBuf, path: PathBuf },
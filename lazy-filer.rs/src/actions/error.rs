@@ -0,0 +1,163 @@
+use super::{NvimErr, NvimWtr};
+use nvim_router::nvim_rs::Neovim;
+use nvim_router::nvim_rs::Value;
+
+use std::fmt;
+use std::io::{Error as IoErr, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    Io {
+        op: Operation,
+        path: PathBuf,
+        source: IoErr,
+    },
+    InvalidInput(String),
+    Nvim(NvimErr),
+}
+
+#[derive(Debug, Clone)]
+pub enum Operation {
+    Create,
+    Delete,
+    Rename { to: PathBuf },
+    Copy { to: PathBuf },
+    Link { to: PathBuf },
+    ReadDir,
+    Trash,
+    Restore,
+    Chmod,
+    LoadState,
+    SaveState,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LogLevel {
+    Warn,
+    Error,
+}
+
+impl Error {
+    pub fn io(op: Operation, path: &Path, source: IoErr) -> Self {
+        Self::Io {
+            op,
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn already_exists(op: Operation, path: &Path) -> Self {
+        Self::io(op, path, ErrorKind::AlreadyExists.into())
+    }
+
+    fn level(&self) -> LogLevel {
+        match self {
+            Self::Io { source, .. } => match source.kind() {
+                ErrorKind::NotFound | ErrorKind::AlreadyExists => LogLevel::Warn,
+                _ => LogLevel::Error,
+            },
+            Self::InvalidInput(_) => LogLevel::Warn,
+            Self::Nvim(_) => LogLevel::Error,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut inner = vec![
+            (Value::from("message"), Value::from(self.to_string())),
+            (Value::from("level"), Value::from(self.level().as_str())),
+        ];
+
+        if let Self::Io { op, path, source } = self {
+            let path = path.display().to_string();
+            let kind = source.kind().to_string();
+
+            inner.push((Value::from("operation"), Value::from(op.as_str())));
+            inner.push((Value::from("path"), Value::from(path)));
+            inner.push((Value::from("kind"), Value::from(kind)));
+
+            if let Operation::Rename { to } | Operation::Copy { to } | Operation::Link { to } = op {
+                let to = to.display().to_string();
+                inner.push((Value::from("to"), Value::from(to)));
+            }
+        }
+
+        Value::Map(inner)
+    }
+
+    pub async fn notify(&self, nvim: &Neovim<NvimWtr>) -> Result<(), NvimErr> {
+        nvim.exec_lua(
+            "require('lazy-filer.call_lua').notify(...)",
+            vec![
+                Value::from(self.to_string()),
+                Value::from(self.level().as_str()),
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl From<NvimErr> for Error {
+    fn from(value: NvimErr) -> Self {
+        Self::Nvim(value)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { op, path, source } => {
+                let path = path.display();
+                match op {
+                    Operation::Rename { to } | Operation::Copy { to } | Operation::Link { to } => {
+                        let to = to.display();
+                        let op = op.as_str();
+                        write!(f, "lazy-filer: cannot {op} {path} to {to}: {source}")
+                    }
+                    _ => write!(f, "lazy-filer: cannot {} {path}: {source}", op.as_str()),
+                }
+            }
+            Self::InvalidInput(msg) => write!(f, "lazy-filer: {msg}"),
+            Self::Nvim(e) => write!(f, "lazy-filer: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::InvalidInput(_) => None,
+            Self::Nvim(e) => Some(e),
+        }
+    }
+}
+
+impl Operation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Delete => "delete",
+            Self::Rename { .. } => "rename",
+            Self::Copy { .. } => "copy",
+            Self::Link { .. } => "link",
+            Self::ReadDir => "read",
+            Self::Trash => "trash",
+            Self::Restore => "restore",
+            Self::Chmod => "change the mode of",
+            Self::LoadState => "load the saved state of",
+            Self::SaveState => "save the state of",
+        }
+    }
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 
-use super::renderer::{Level, LineIdx};
+use super::renderer::{FileType, Level, LineIdx};
 use super::utils;
-use super::{Action, States};
+use super::{Action, Error, States};
 
 use std::path::Path;
 
@@ -15,14 +15,15 @@ pub struct ExpandDir {
 impl Action for ExpandDir {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
-        let Some((level, path)) = states
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some((level, path, more)) = states
             .actions
             .rendered_lines
             .get(self.line_idx)
             .and_then(|item| {
-                if item.metadata.is_dir() {
-                    Some((item.level, item.path.to_path_buf()))
+                let more = matches!(item.metadata.file_type, FileType::More(_));
+                if item.metadata.is_dir() || more {
+                    Some((item.level, item.path.to_path_buf(), more))
                 } else {
                     None
                 }
@@ -32,7 +33,11 @@ impl Action for ExpandDir {
             return Ok(());
         };
 
-        expand_dir(self.line_idx, &self.nvim, level, &path, states).await?;
+        if more {
+            expand_rest(&self.nvim, &path, states).await?;
+        } else {
+            expand_dir(self.line_idx, &self.nvim, level, &path, states).await?;
+        }
 
         Ok(())
     }
@@ -44,42 +49,77 @@ pub async fn expand_dir(
     level: Level,
     path: &Path,
     states: &States,
-) -> Result<(), NvimErr> {
+) -> Result<(), Error> {
     if states.actions.expanded_dir.contains(path).await {
-        states.actions.expanded_dir.remove(path).await;
-
-        states
-            .actions
-            .rendered_lines
-            .edit(nvim)
-            .remove_range(|lines| {
-                let range = utils::find_in_dir(path, lines);
-                if range.start == range.end {
-                    range
-                } else {
-                    (range.start + 1)..(range.end)
-                }
-            })
-            .await?;
+        collapse_dir(nvim, path, states).await?;
     } else {
         states.actions.expanded_dir.insert(path.to_path_buf()).await;
         let expanded_dir = states.actions.expanded_dir.clone().await;
+        let view = states.actions.view.get().await;
 
         let target_dir = utils::get_entries(&states.root_file, path).await;
-        target_dir.update_with_readdir().await?;
+        let insert_children = || async {
+            let stream = target_dir
+                .flatten(level, &view)
+                .filter(|path| expanded_dir.contains(path))
+                .await;
 
-        let stream = target_dir
-            .flatten(level)
-            .filter(|path| expanded_dir.contains(path))
-            .await;
+            states
+                .actions
+                .rendered_lines
+                .edit(nvim)
+                .insert(stream, line_idx + 1)
+                .await?;
 
-        states
-            .actions
-            .rendered_lines
-            .edit(nvim)
-            .insert(stream, line_idx + 1)
-            .await?;
+            Ok(())
+        };
+
+        let inserted = target_dir.update_with_readdir_or(insert_children).await?;
+        if inserted {
+            target_dir
+                .render_children(nvim, &states.actions.rendered_lines, &expanded_dir, &view)
+                .await?;
+        } else {
+            insert_children().await?;
+        }
     }
 
     Ok(())
 }
+
+pub async fn collapse_dir(
+    nvim: &Neovim<NvimWtr>,
+    path: &Path,
+    states: &States,
+) -> Result<(), Error> {
+    states.actions.expanded_dir.remove(path).await;
+
+    states
+        .actions
+        .rendered_lines
+        .edit(nvim)
+        .remove_range(|lines| {
+            let range = utils::find_in_dir(path, lines);
+            if range.start == range.end {
+                range
+            } else {
+                (range.start + 1)..(range.end)
+            }
+        })
+        .await?;
+
+    Ok(())
+}
+
+// Renders the entries of `dir` beyond the limit.
+pub async fn expand_rest(nvim: &Neovim<NvimWtr>, dir: &Path, states: &States) -> Result<(), Error> {
+    let view = states.actions.view.uncap(dir).await;
+    let expanded_dir = states.actions.expanded_dir.clone().await;
+
+    let target_dir = utils::get_entries(&states.root_file, dir).await;
+    target_dir
+        .render_children(nvim, &states.actions.rendered_lines, &expanded_dir, &view)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::Error;
+
+use std::path::{Path, PathBuf};
+
+// A change made to the filesystem by the filer. Neovim is told about it before and after it is
+// made, so that buffers follow renamed files and language servers can update references to them.
+#[derive(Debug, Clone)]
+pub enum FileOp {
+    Create {
+        path: PathBuf,
+        dir: bool,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+        dir: bool,
+    },
+    Delete {
+        path: PathBuf,
+        dir: bool,
+    },
+}
+
+impl FileOp {
+    // Must be called while `from` still exists.
+    pub fn rename(from: &Path, to: &Path) -> Self {
+        Self::Rename {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            dir: is_dir(from),
+        }
+    }
+
+    // Must be called once `path` exists.
+    pub fn created(path: &Path) -> Self {
+        Self::Create {
+            path: path.to_path_buf(),
+            dir: is_dir(path),
+        }
+    }
+
+    // The copy of `from` made at `to`.
+    pub fn copy(from: &Path, to: &Path) -> Self {
+        Self::Create {
+            path: to.to_path_buf(),
+            dir: is_dir(from),
+        }
+    }
+
+    // Must be called while `path` still exists.
+    pub fn delete(path: &Path) -> Self {
+        Self::Delete {
+            path: path.to_path_buf(),
+            dir: is_dir(path),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        fn path_val(path: &Path) -> Value {
+            Value::from(path.to_string_lossy().into_owned())
+        }
+
+        let (kind, dir, mut inner) = match self {
+            Self::Create { path, dir } => ("create", dir, vec![("path", path_val(path))]),
+            Self::Rename { from, to, dir } => (
+                "rename",
+                dir,
+                vec![("old", path_val(from)), ("new", path_val(to))],
+            ),
+            Self::Delete { path, dir } => ("delete", dir, vec![("path", path_val(path))]),
+        };
+        inner.push(("kind", Value::from(kind)));
+        inner.push(("dir", Value::from(*dir)));
+
+        Value::Map(
+            inner
+                .into_iter()
+                .map(|(key, val)| (Value::from(key), val))
+                .collect(),
+        )
+    }
+}
+
+// Symbolic links are files, even if they point to a directory.
+fn is_dir(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|meta| meta.is_dir())
+}
+
+// Sends the will* requests to language servers and applies the edits they return. This waits
+// for the servers, up to the timeout set in Lua.
+pub async fn will(nvim: &Neovim<NvimWtr>, ops: &[FileOp]) -> Result<(), Error> {
+    call(nvim, "will_change_files", ops).await
+}
+
+// Retargets or wipes the affected buffers and sends the did* notifications. The operations are
+// made by then, so a failure is only notified: the caller still records and shows them.
+pub async fn did(nvim: &Neovim<NvimWtr>, ops: &[FileOp]) {
+    if let Err(e) = call(nvim, "did_change_files", ops).await {
+        e.notify(nvim).await.ok();
+    }
+}
+
+async fn call(nvim: &Neovim<NvimWtr>, func: &str, ops: &[FileOp]) -> Result<(), Error> {
+    if ops.is_empty() {
+        return Ok(());
+    }
+
+    let ops = ops.iter().map(FileOp::to_value).collect();
+    nvim.exec_lua(
+        &format!("require('lazy-filer.call_lua').{func}(...)"),
+        vec![Value::Array(ops)],
+    )
+    .await?;
+
+    Ok(())
+}
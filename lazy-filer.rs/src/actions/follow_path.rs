@@ -0,0 +1,87 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::expand_dir::collapse_dir;
+use super::reveal_match::reveal;
+use super::{Action, DirArg, Error, States};
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+// Keeps the tree in sync with the current buffer: its parents are expanded, and directories
+// expanded for the previous buffer are collapsed unless they are still needed.
+pub struct FollowPath {
+    pub nvim: Neovim<NvimWtr>,
+    pub dir: DirArg,
+    pub path: PathBuf,
+}
+
+impl Action for FollowPath {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let root = self.dir.as_path();
+        let expanded_dir = &states.actions.expanded_dir;
+
+        // Nothing is expanded for a file that does not exist yet, so that its directories are
+        // not made up in the cache.
+        let exists = self.path.symlink_metadata().is_ok();
+        let ancestors: BTreeSet<PathBuf> = if exists && self.path.starts_with(root) {
+            self.path
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| dir.starts_with(root) && dir.is_dir())
+                .map(Path::to_path_buf)
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+
+        let (keep, collapse): (BTreeSet<_>, BTreeSet<_>) = expanded_dir
+            .take_auto()
+            .await
+            .into_iter()
+            .partition(|dir| ancestors.contains(dir));
+
+        // Parents come first, so the lines of nested directories go away with them.
+        for dir in &collapse {
+            collapse_dir(&self.nvim, dir, states).await?;
+        }
+
+        let expanded = expanded_dir.clone().await;
+        let line = if ancestors.is_empty() {
+            None
+        } else {
+            reveal(&self.nvim, states, root, &self.path).await?
+        };
+
+        let added = ancestors.into_iter().filter(|dir| !expanded.contains(dir));
+        expanded_dir
+            .extend_auto(keep.into_iter().chain(added))
+            .await;
+
+        let current = line.map(|_| self.path.clone());
+        let prev = states
+            .actions
+            .rendered_lines
+            .current()
+            .replace(current.clone())
+            .await;
+
+        for path in [prev, current].into_iter().flatten() {
+            states
+                .actions
+                .rendered_lines
+                .edit(&self.nvim)
+                .redraw(
+                    |lines| match lines.iter().position(|item| item.path == path) {
+                        Some(idx) => idx..(idx + 1),
+                        None => 0..0,
+                    },
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,7 @@
-use super::NvimErr;
-
 use nvim_router::nvim_rs::Value;
 
 use super::renderer::LineIdx;
-use super::{Action, States};
+use super::{Action, Error, States};
 
 use std::path::Path;
 
@@ -14,7 +12,7 @@ pub struct GetDir {
 impl Action for GetDir {
     type Resp = Value;
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let Some(path) = states
             .actions
             .rendered_lines
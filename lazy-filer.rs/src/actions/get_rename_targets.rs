@@ -0,0 +1,90 @@
+use nvim_router::nvim_rs::Value;
+
+use super::renderer::LineIdx;
+use super::utils;
+use super::{Action, Error, States};
+use crate::fs::File;
+
+use std::path::{Path, PathBuf};
+
+pub struct GetRenameTargets {
+    pub line_idx: LineIdx,
+}
+
+impl Action for GetRenameTargets {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let selection = states.actions.rendered_lines.selection().get().await;
+
+        let (dir, paths) = if selection.is_empty() {
+            let Some(dir) = states
+                .actions
+                .rendered_lines
+                .get(self.line_idx)
+                .and_then(|item| {
+                    if item.metadata.is_dir() {
+                        Some(item.path.to_path_buf())
+                    } else {
+                        item.path.parent().map(Path::to_path_buf)
+                    }
+                })
+                .await
+            else {
+                return Ok(Value::Nil);
+            };
+
+            let target_dir = utils::get_entries(&states.root_file, &dir).await;
+            target_dir.update_with_readdir().await?;
+
+            let mut matcher = states.actions.view.get().await.filter.matcher();
+            let paths: Vec<PathBuf> = target_dir
+                .children()
+                .await
+                .into_iter()
+                .filter(|(path, file)| {
+                    let is_dir = matches!(file.follow_link(), File::Directory { .. });
+                    matcher.is_visible(path, is_dir)
+                })
+                .map(|(path, _)| path)
+                .collect();
+
+            (dir, paths)
+        } else {
+            let paths: Vec<PathBuf> = selection.into_iter().collect();
+            let Some(dir) = common_parent(&paths) else {
+                return Ok(Value::Nil);
+            };
+
+            (dir, paths)
+        };
+
+        let Some(dir_val) = dir.to_str().map(Value::from) else {
+            return Ok(Value::Nil);
+        };
+
+        let names = paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(&dir).ok())
+            .filter_map(|path| path.to_str())
+            .map(Value::from)
+            .collect();
+
+        Ok(Value::Map(vec![
+            (Value::from("dir"), dir_val),
+            (Value::from("names"), Value::Array(names)),
+        ]))
+    }
+}
+
+fn common_parent(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut dir = paths.first()?.parent()?.to_path_buf();
+
+    while !paths.iter().all(|path| path.starts_with(&dir)) {
+        if !dir.pop() {
+            return None;
+        }
+    }
+
+    Some(dir)
+}
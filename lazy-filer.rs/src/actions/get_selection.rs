@@ -0,0 +1,22 @@
+use nvim_router::nvim_rs::Value;
+
+use super::{Action, Error, States};
+
+pub struct GetSelection;
+
+impl Action for GetSelection {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let selection = states.actions.rendered_lines.selection().get().await;
+
+        // Every path is listed, even one that is not UTF-8, so that the count shown matches
+        // what is acted on.
+        let paths = selection
+            .iter()
+            .map(|path| Value::from(path.to_string_lossy().into_owned()))
+            .collect();
+
+        Ok(Value::Array(paths))
+    }
+}
@@ -0,0 +1,82 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::States;
+use crate::git::{self, RepoStatus, Status};
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use std::sync::Arc;
+use tokio::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Default)]
+pub struct Repos {
+    statuses: BTreeMap<PathBuf, RepoStatus>,
+    generations: BTreeMap<PathBuf, u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GitCache(Arc<Mutex<Repos>>);
+
+impl GitCache {
+    pub async fn lock(&self) -> MutexGuard<'_, Repos> {
+        self.0.lock().await
+    }
+
+    async fn begin(&self, root: &Path) -> u64 {
+        let mut lock = self.0.lock().await;
+        let generation = lock.generations.entry(root.to_path_buf()).or_default();
+        *generation += 1;
+        *generation
+    }
+
+    // A result is dropped if another update of the same repository was started after it.
+    async fn finish(&self, root: &Path, generation: u64, status: RepoStatus) -> bool {
+        let mut lock = self.0.lock().await;
+        if lock.generations.get(root) != Some(&generation) {
+            return false;
+        }
+        lock.statuses.insert(root.to_path_buf(), status);
+        true
+    }
+}
+
+impl Repos {
+    pub fn get(&self, path: &Path, is_dir: bool) -> Option<Status> {
+        let (_, status) = self
+            .statuses
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())?;
+
+        status.get(path, is_dir)
+    }
+}
+
+pub fn update(nvim: &Neovim<NvimWtr>, states: &States, dir: &Path) {
+    let Some(root) = git::repo_root(dir) else {
+        return;
+    };
+
+    let nvim = nvim.clone();
+    let states = states.clone();
+
+    tokio::spawn(async move {
+        let lines = &states.actions.rendered_lines;
+        let generation = lines.git().begin(&root).await;
+
+        let status = {
+            let root = root.clone();
+            tokio::task::spawn_blocking(move || RepoStatus::read(&root)).await
+        };
+        // Without git, or outside a work tree, entries are simply left undecorated.
+        let Ok(Ok(status)) = status else {
+            return;
+        };
+
+        if lines.git().finish(&root, generation, status).await {
+            lines.edit(&nvim).redraw(|_| ..).await.ok();
+        }
+    });
+}
@@ -0,0 +1,191 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::git_status;
+use super::reveal_match::set_cursor;
+use super::utils;
+use super::{Error, States};
+
+use std::collections::{BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const MAX_LEN: usize = 100;
+
+// A displayed root, with what was expanded under it and the entry under the cursor.
+#[derive(Debug, Clone)]
+struct Place {
+    root: PathBuf,
+    expanded: BTreeSet<PathBuf>,
+    cursor: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Back,
+    Forward,
+}
+
+#[derive(Debug, Default)]
+struct Stacks {
+    back: VecDeque<Place>,
+    forward: Vec<Place>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History(Arc<Mutex<Stacks>>);
+
+impl History {
+    async fn push(&self, place: Place) {
+        let mut lock = self.0.lock().await;
+        lock.forward.clear();
+        lock.back.push_back(place);
+        if lock.back.len() > MAX_LEN {
+            lock.back.pop_front();
+        }
+    }
+
+    async fn pop(&self, direction: Direction) -> Option<Place> {
+        let mut lock = self.0.lock().await;
+        match direction {
+            Direction::Back => lock.back.pop_back(),
+            Direction::Forward => lock.forward.pop(),
+        }
+    }
+
+    async fn push_opposite(&self, direction: Direction, place: Place) {
+        let mut lock = self.0.lock().await;
+        match direction {
+            Direction::Back => lock.forward.push(place),
+            Direction::Forward => {
+                lock.back.push_back(place);
+                if lock.back.len() > MAX_LEN {
+                    lock.back.pop_front();
+                }
+            }
+        }
+    }
+}
+
+// Called before the displayed root changes to `new_root`.
+pub async fn record(nvim: &Neovim<NvimWtr>, states: &States, new_root: &Path) -> Result<(), Error> {
+    let Some(root) = current_root(states).await else {
+        return Ok(());
+    };
+    if root == new_root {
+        return Ok(());
+    }
+
+    let place = snapshot(nvim, states, root).await?;
+    states.actions.history.push(place).await;
+
+    Ok(())
+}
+
+// Displays the previous (or next) root again. Directories still in the cache are not read.
+// Returns the root, or None if there is nowhere to go.
+pub async fn travel(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    direction: Direction,
+) -> Result<Option<PathBuf>, Error> {
+    let history = &states.actions.history;
+
+    let Some(place) = history.pop(direction).await else {
+        return Ok(None);
+    };
+    if let Some(current) = current_root(states).await {
+        let current = snapshot(nvim, states, current).await?;
+        history.push_opposite(direction, current).await;
+    }
+
+    let Place {
+        root,
+        expanded,
+        cursor,
+    } = place;
+
+    states
+        .actions
+        .expanded_dir
+        .lock()
+        .then(|expanded_dir| {
+            expanded_dir.retain(|dir| !dir.starts_with(&root));
+            expanded_dir.insert(root.clone());
+            expanded_dir.extend(expanded);
+        })
+        .await;
+    git_status::update(nvim, states, &root);
+    let expanded_dir = states.actions.expanded_dir.clone().await;
+    let view = states.actions.view.get().await;
+
+    // A directory that has gone away is not looked up, which would put it back into the cache.
+    let dirs = expanded_dir
+        .iter()
+        .filter(|dir| dir.starts_with(&root) && dir.is_dir());
+    for dir in dirs {
+        let target_dir = utils::get_entries(&states.root_file, dir).await;
+        if !target_dir.is_read().await {
+            target_dir.update_with_readdir().await.ok();
+        }
+    }
+
+    let target_dir = utils::get_entries(&states.root_file, &root).await;
+    target_dir
+        .render_entire_buffer(nvim, &states.actions.rendered_lines, &expanded_dir, &view)
+        .await?;
+
+    if let Some(cursor) = cursor
+        && let Some(idx) = states.actions.rendered_lines.position(&cursor).await
+    {
+        set_cursor(nvim, states.actions.instance, idx).await?;
+    }
+
+    Ok(Some(root))
+}
+
+async fn current_root(states: &States) -> Option<PathBuf> {
+    states
+        .actions
+        .rendered_lines
+        .get(0.into())
+        .and_then(|item| Some(item.path.to_path_buf()))
+        .await
+}
+
+async fn snapshot(nvim: &Neovim<NvimWtr>, states: &States, root: PathBuf) -> Result<Place, Error> {
+    let lines = &states.actions.rendered_lines;
+
+    let line = nvim
+        .exec_lua(
+            "return require('lazy-filer.call_lua').get_cursor(...)",
+            vec![Value::from(states.actions.instance)],
+        )
+        .await?;
+    let cursor = match line.as_i64() {
+        Some(line) => {
+            lines
+                .get((line - 1).into())
+                .and_then(|item| Some(item.path.to_path_buf()))
+                .await
+        }
+        None => None,
+    };
+
+    let expanded = states
+        .actions
+        .expanded_dir
+        .clone()
+        .await
+        .into_iter()
+        .filter(|dir| dir.starts_with(&root))
+        .collect();
+
+    Ok(Place {
+        root,
+        expanded,
+        cursor,
+    })
+}
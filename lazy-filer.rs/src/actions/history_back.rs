@@ -0,0 +1,20 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::history::{self, Direction};
+use super::{Action, Error, States};
+
+pub struct HistoryBack {
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for HistoryBack {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let root = history::travel(&self.nvim, states, Direction::Back).await?;
+        Ok(root.map_or(Value::Nil, |root| {
+            Value::from(root.to_string_lossy().into_owned())
+        }))
+    }
+}
@@ -0,0 +1,465 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::create_link::make_link;
+use super::file_ops::{self, FileOp};
+use super::refresh::refresh_trees;
+use super::states::LinkKind;
+use super::utils;
+use super::{Error, Operation, States};
+use crate::trash::{self, TrashedFile};
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fs::{DirBuilder, OpenOptions};
+use std::os::unix::fs::{DirBuilderExt as _, OpenOptionsExt as _};
+use std::path::{Path, PathBuf};
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const MAX_LEN: usize = 100;
+
+#[derive(Debug, Clone)]
+pub enum Mutation {
+    // Undo moves the created entry to the trash, as it may have been written since, and redo
+    // restores it from there. `trashed` is set while the creation is undone.
+    Create {
+        path: PathBuf,
+        dir: bool,
+        trashed: Option<TrashedFile>,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    // Undone and redone like `Create`: the pasted tree may have been changed since.
+    Copy {
+        from: PathBuf,
+        to: PathBuf,
+        trashed: Option<TrashedFile>,
+    },
+    Link {
+        target: PathBuf,
+        path: PathBuf,
+        kind: LinkKind,
+    },
+    Trash {
+        path: PathBuf,
+        trashed: TrashedFile,
+    },
+    Batch(Vec<Mutation>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Default)]
+struct Stacks {
+    undo: VecDeque<Mutation>,
+    redo: Vec<Mutation>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Journal(Arc<Mutex<Stacks>>);
+
+impl Journal {
+    pub async fn record(&self, mutation: Mutation) {
+        let mut lock = self.0.lock().await;
+        lock.redo.clear();
+
+        if lock.undo.len() >= MAX_LEN {
+            lock.undo.pop_front();
+        }
+        lock.undo.push_back(mutation);
+    }
+
+    async fn pop(&self, direction: Direction) -> Option<Mutation> {
+        let mut lock = self.0.lock().await;
+        match direction {
+            Direction::Undo => lock.undo.pop_back(),
+            Direction::Redo => lock.redo.pop(),
+        }
+    }
+
+    async fn push(&self, direction: Direction, mutation: Mutation) {
+        let mut lock = self.0.lock().await;
+        match direction {
+            Direction::Undo => lock.undo.push_back(mutation),
+            Direction::Redo => lock.redo.push(mutation),
+        }
+    }
+}
+
+impl Direction {
+    fn reverse(self) -> Self {
+        match self {
+            Self::Undo => Self::Redo,
+            Self::Redo => Self::Undo,
+        }
+    }
+}
+
+pub async fn replay(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    direction: Direction,
+) -> Result<(), Error> {
+    let journal = &states.actions.journal;
+
+    let Some(mutation) = journal.pop(direction).await else {
+        return Ok(());
+    };
+
+    let ops = mutation.file_ops(direction);
+    if let Err(e) = file_ops::will(nvim, &ops).await {
+        journal.push(direction, mutation).await;
+        return Err(e);
+    }
+
+    let applied = match mutation.clone().apply(direction) {
+        Ok(applied) => applied,
+        Err(e) => {
+            journal.push(direction, mutation).await;
+            return Err(e);
+        }
+    };
+
+    states
+        .actions
+        .expanded_dir
+        .lock()
+        .then(|expanded_dir| applied.move_expanded(expanded_dir, direction))
+        .await;
+
+    let dirs = applied.parents();
+    journal.push(direction.reverse(), applied).await;
+    file_ops::did(nvim, &ops).await;
+
+    refresh_trees(nvim, states, &dirs).await?;
+
+    Ok(())
+}
+
+impl Mutation {
+    fn apply(self, direction: Direction) -> Result<Self, Error> {
+        match direction {
+            Direction::Undo => self.undo(),
+            Direction::Redo => self.redo(),
+        }
+    }
+
+    // Undoing a batch replays its steps backwards. If one of them fails, the steps already
+    // replayed are reverted so that the batch stays all-or-nothing.
+    fn apply_all(mutations: Vec<Self>, direction: Direction) -> Result<Vec<Self>, Error> {
+        let ordered: Vec<Self> = match direction {
+            Direction::Undo => mutations.into_iter().rev().collect(),
+            Direction::Redo => mutations,
+        };
+
+        let mut done = Vec::with_capacity(ordered.len());
+        for mutation in ordered {
+            match mutation.apply(direction) {
+                Ok(mutation) => done.push(mutation),
+                Err(e) => {
+                    for mutation in done.into_iter().rev() {
+                        mutation.apply(direction.reverse()).ok();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        if let Direction::Undo = direction {
+            done.reverse();
+        }
+        Ok(done)
+    }
+
+    pub fn move_expanded(&self, expanded_dir: &mut BTreeSet<PathBuf>, direction: Direction) {
+        match (self, direction) {
+            (Self::Rename { from, to }, Direction::Undo) => move_expanded(expanded_dir, to, from),
+            (Self::Rename { from, to }, Direction::Redo) => move_expanded(expanded_dir, from, to),
+            (Self::Batch(mutations), Direction::Undo) => {
+                for mutation in mutations.iter().rev() {
+                    mutation.move_expanded(expanded_dir, direction);
+                }
+            }
+            (Self::Batch(mutations), Direction::Redo) => {
+                for mutation in mutations {
+                    mutation.move_expanded(expanded_dir, direction);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // What replaying the mutation does to the filesystem. Must be called before it is replayed.
+    fn file_ops(&self, direction: Direction) -> Vec<FileOp> {
+        let undo = matches!(direction, Direction::Undo);
+
+        match self {
+            Self::Create {
+                path,
+                dir,
+                trashed: _,
+            } if undo => vec![FileOp::Delete {
+                path: path.clone(),
+                dir: *dir,
+            }],
+            Self::Create {
+                path,
+                dir,
+                trashed: _,
+            } => vec![FileOp::Create {
+                path: path.clone(),
+                dir: *dir,
+            }],
+            Self::Rename { from, to } if undo => vec![FileOp::rename(to, from)],
+            Self::Rename { from, to } => vec![FileOp::rename(from, to)],
+            Self::Copy {
+                from: _,
+                to,
+                trashed: _,
+            } if undo => vec![FileOp::delete(to)],
+            Self::Copy {
+                from,
+                to,
+                trashed: _,
+            } => vec![FileOp::copy(from, to)],
+            Self::Link {
+                target: _,
+                path,
+                kind: _,
+            } if undo => vec![FileOp::delete(path)],
+            Self::Link {
+                target: _,
+                path,
+                kind: _,
+            } => vec![FileOp::Create {
+                path: path.clone(),
+                dir: false,
+            }],
+            Self::Trash { path, trashed } if undo => {
+                vec![FileOp::copy(&trashed.file_path(), path)]
+            }
+            Self::Trash { path, trashed: _ } => vec![FileOp::delete(path)],
+            Self::Batch(mutations) if undo => mutations
+                .iter()
+                .rev()
+                .flat_map(|mutation| mutation.file_ops(direction))
+                .collect(),
+            Self::Batch(mutations) => mutations
+                .iter()
+                .flat_map(|mutation| mutation.file_ops(direction))
+                .collect(),
+        }
+    }
+
+    fn undo(self) -> Result<Self, Error> {
+        match self {
+            Self::Create {
+                path,
+                dir,
+                trashed: _,
+            } => {
+                let trashed =
+                    trash::trash(&path).map_err(|e| Error::io(Operation::Trash, &path, e))?;
+
+                Ok(Self::Create {
+                    path,
+                    dir,
+                    trashed: Some(trashed),
+                })
+            }
+            Self::Rename { from, to } => {
+                rename(&to, &from)?;
+                Ok(Self::Rename { from, to })
+            }
+            Self::Copy {
+                from,
+                to,
+                trashed: _,
+            } => {
+                let trashed = trash::trash(&to).map_err(|e| Error::io(Operation::Trash, &to, e))?;
+
+                Ok(Self::Copy {
+                    from,
+                    to,
+                    trashed: Some(trashed),
+                })
+            }
+            Self::Link { target, path, kind } => {
+                std::fs::remove_file(&path).map_err(|e| Error::io(Operation::Delete, &path, e))?;
+                Ok(Self::Link { target, path, kind })
+            }
+            Self::Trash { path, trashed } => {
+                trashed
+                    .restore()
+                    .map_err(|e| Error::io(Operation::Restore, &path, e))?;
+
+                Ok(Self::Trash { path, trashed })
+            }
+            Self::Batch(mutations) => Self::apply_all(mutations, Direction::Undo).map(Self::Batch),
+        }
+    }
+
+    fn redo(self) -> Result<Self, Error> {
+        match self {
+            Self::Create {
+                path,
+                dir,
+                trashed: Some(trashed),
+            } => {
+                trashed
+                    .restore()
+                    .map_err(|e| Error::io(Operation::Restore, &path, e))?;
+
+                Ok(Self::Create {
+                    path,
+                    dir,
+                    trashed: None,
+                })
+            }
+            Self::Create {
+                path,
+                dir,
+                trashed: None,
+            } => {
+                let res = if dir {
+                    let mut builder = DirBuilder::new();
+                    builder.mode(0o775);
+                    builder.create(&path)
+                } else {
+                    let mut opts = OpenOptions::new();
+                    opts.write(true).create_new(true).mode(0o664);
+                    opts.open(&path).map(|_| ())
+                };
+                res.map_err(|e| Error::io(Operation::Create, &path, e))?;
+
+                Ok(Self::Create {
+                    path,
+                    dir,
+                    trashed: None,
+                })
+            }
+            Self::Rename { from, to } => {
+                rename(&from, &to)?;
+                Ok(Self::Rename { from, to })
+            }
+            Self::Copy {
+                from,
+                to,
+                trashed: Some(trashed),
+            } => {
+                trashed
+                    .restore()
+                    .map_err(|e| Error::io(Operation::Restore, &to, e))?;
+
+                Ok(Self::Copy {
+                    from,
+                    to,
+                    trashed: None,
+                })
+            }
+            Self::Copy {
+                from,
+                to,
+                trashed: None,
+            } => {
+                let op = Operation::Copy { to: to.clone() };
+                if to.symlink_metadata().is_ok() {
+                    return Err(Error::already_exists(op, &from));
+                }
+                utils::copy_all(&from, &to).map_err(|e| Error::io(op, &from, e))?;
+
+                Ok(Self::Copy {
+                    from,
+                    to,
+                    trashed: None,
+                })
+            }
+            Self::Link { target, path, kind } => {
+                let op = Operation::Link { to: path.clone() };
+                if path.symlink_metadata().is_ok() {
+                    return Err(Error::already_exists(op, &target));
+                }
+                make_link(kind, &target, &path).map_err(|e| Error::io(op, &target, e))?;
+
+                Ok(Self::Link { target, path, kind })
+            }
+            Self::Trash { path, trashed: _ } => {
+                let trashed =
+                    trash::trash(&path).map_err(|e| Error::io(Operation::Trash, &path, e))?;
+
+                Ok(Self::Trash { path, trashed })
+            }
+            Self::Batch(mutations) => Self::apply_all(mutations, Direction::Redo).map(Self::Batch),
+        }
+    }
+
+    pub fn parents(&self) -> BTreeSet<PathBuf> {
+        let paths = match self {
+            Self::Create {
+                path,
+                dir: _,
+                trashed: _,
+            }
+            | Self::Trash { path, trashed: _ } => vec![path],
+            Self::Copy {
+                from: _,
+                to,
+                trashed: _,
+            } => vec![to],
+            Self::Link {
+                target: _,
+                path,
+                kind: _,
+            } => vec![path],
+            Self::Rename { from, to } => vec![from, to],
+            Self::Batch(mutations) => {
+                return mutations.iter().flat_map(Self::parents).collect();
+            }
+        };
+
+        paths
+            .into_iter()
+            .filter_map(|path| path.parent())
+            .map(Path::to_path_buf)
+            .collect()
+    }
+}
+
+fn rename(from: &Path, to: &Path) -> Result<(), Error> {
+    let op = Operation::Rename {
+        to: to.to_path_buf(),
+    };
+    if to.symlink_metadata().is_ok() {
+        return Err(Error::already_exists(op, from));
+    }
+
+    std::fs::rename(from, to).map_err(|e| Error::io(op, from, e))
+}
+
+fn move_expanded(expanded_dir: &mut BTreeSet<PathBuf>, from: &Path, to: &Path) {
+    let moved: Vec<_> = expanded_dir
+        .iter()
+        .filter(|path| path.starts_with(from))
+        .cloned()
+        .collect();
+
+    for path in moved {
+        expanded_dir.remove(&path);
+        match path.strip_prefix(from) {
+            Ok(rest) if rest.as_os_str().is_empty() => {
+                expanded_dir.insert(to.to_path_buf());
+            }
+            Ok(rest) => {
+                expanded_dir.insert(to.join(rest));
+            }
+            Err(_) => {}
+        }
+    }
+}
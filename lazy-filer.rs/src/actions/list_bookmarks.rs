@@ -0,0 +1,30 @@
+use nvim_router::nvim_rs::Value;
+
+use super::utils;
+use super::{Action, Error, Operation, States};
+use crate::bookmarks;
+
+pub struct ListBookmarks;
+
+impl Action for ListBookmarks {
+    type Resp = Value;
+
+    async fn run(&self, _states: &States) -> Result<Self::Resp, Error> {
+        let file = utils::bookmarks_file()?;
+        let bookmarks =
+            bookmarks::load(&file).map_err(|e| Error::io(Operation::LoadState, &file, e))?;
+
+        let bookmarks = bookmarks
+            .into_iter()
+            .map(|(name, path)| {
+                let path = path.to_string_lossy().into_owned();
+                Value::Map(vec![
+                    (Value::from("name"), Value::from(name)),
+                    (Value::from("path"), Value::from(path)),
+                ])
+            })
+            .collect();
+
+        Ok(Value::Array(bookmarks))
+    }
+}
@@ -0,0 +1,33 @@
+use nvim_router::nvim_rs::Value;
+
+use super::{Action, DirArg, Error, States};
+use crate::trash::{self, TrashEntry};
+
+pub struct ListTrash {
+    pub dir: DirArg,
+}
+
+impl Action for ListTrash {
+    type Resp = Value;
+
+    async fn run(&self, _states: &States) -> Result<Self::Resp, Error> {
+        let trash_dirs = trash::trash_dirs(self.dir.as_path());
+        let entries = trash::list(&trash_dirs);
+
+        Ok(Value::Array(entries.iter().map(entry_to_val).collect()))
+    }
+}
+
+fn entry_to_val(entry: &TrashEntry) -> Value {
+    let info = entry.file.info_path().display().to_string();
+    let original = entry.original.display().to_string();
+
+    Value::Map(vec![
+        (Value::from("id"), Value::from(info)),
+        (Value::from("path"), Value::from(original)),
+        (
+            Value::from("deleted_at"),
+            Value::from(entry.deleted_at.as_str()),
+        ),
+    ])
+}
@@ -1,20 +1,62 @@
 use super::{NvimErr, NvimWtr};
 use crate::states::States;
 
+mod error;
+mod file_ops;
+mod git_status;
+mod history;
+mod journal;
+mod rename_plan;
 mod renderer;
 mod utils;
+mod watcher;
 
+mod add_bookmark;
+mod bulk_rename;
+mod chmod_entry;
+mod clear_selection;
 mod create_entry;
+mod create_link;
+mod delete_bookmark;
 mod delete_entry;
 mod expand_dir;
+mod follow_path;
 mod get_dir;
 mod get_file_path;
+mod get_rename_targets;
+mod get_selection;
+mod history_back;
+mod history_forward;
+mod list_bookmarks;
+mod list_trash;
 mod move_to_parent;
 mod new_filer;
 mod open_file;
 mod open_or_expand;
+mod paste_entry;
+mod preview;
+mod redo;
+mod redraw;
 mod refresh;
 mod rename_entry;
+mod restore_entry;
+mod reveal_match;
+mod reveal_path;
+mod save_session;
+mod search;
+mod select_glob;
+mod select_range;
+mod set_filter;
+mod set_limit;
+mod set_time_format;
+mod sort_by;
+mod toggle_filter;
+mod toggle_selection;
+mod undo;
+mod update_git_status;
+mod yank_entry;
+
+pub use error::{Error, Operation};
 
 use std::future::Future;
 use std::path::Path;
@@ -36,61 +78,289 @@ impl From<String> for DirArg {
 pub trait Action {
     type Resp;
 
-    fn run(&self, states: &States) -> impl Future<Output = Result<Self::Resp, NvimErr>>;
+    fn run(&self, states: &States) -> impl Future<Output = Result<Self::Resp, Error>>;
 }
 
 pub mod prelude {
     pub use super::Action;
     pub use super::{
-        create_entry::CreateEntry, delete_entry::DeleteEntry, expand_dir::ExpandDir,
-        get_dir::GetDir, get_file_path::GetFilePath, move_to_parent::MoveToParent,
-        new_filer::NewFiler, open_file::OpenFile, open_or_expand::OpenOrExpand, refresh::Refresh,
-        rename_entry::RenameEntry,
+        add_bookmark::AddBookmark, bulk_rename::BulkRename, chmod_entry::ChmodEntry,
+        clear_selection::ClearSelection, create_entry::CreateEntry, create_link::CreateLink,
+        delete_bookmark::DeleteBookmark, delete_entry::DeleteEntry, expand_dir::ExpandDir,
+        follow_path::FollowPath, get_dir::GetDir, get_file_path::GetFilePath,
+        get_rename_targets::GetRenameTargets, get_selection::GetSelection,
+        history_back::HistoryBack, history_forward::HistoryForward, list_bookmarks::ListBookmarks,
+        list_trash::ListTrash, move_to_parent::MoveToParent, new_filer::NewFiler,
+        open_file::OpenFile, open_or_expand::OpenOrExpand, paste_entry::PasteEntry,
+        preview::Preview, redo::Redo, redraw::Redraw, refresh::Refresh, rename_entry::RenameEntry,
+        restore_entry::RestoreEntry, reveal_match::RevealMatch, reveal_path::RevealPath,
+        save_session::SaveSession, search::Search, select_glob::SelectGlob,
+        select_range::SelectRange, set_filter::SetFilter, set_limit::SetLimit,
+        set_time_format::SetTimeFormat, sort_by::SortBy, toggle_filter::ToggleFilter,
+        toggle_selection::ToggleSelection, undo::Undo, update_git_status::UpdateGitStatus,
+        yank_entry::YankEntry,
     };
 }
 
 pub mod states {
+    use super::history::History;
+    use super::journal::Journal;
     use super::renderer::Items;
+    use super::watcher::Watcher;
+    use super::{NvimWtr, States as RootStates};
+    use crate::sort::SortOrder;
+    use crate::visibility::Filter;
+    use nvim_router::nvim_rs::Neovim;
 
     use std::collections::BTreeSet;
     use std::path::{Path, PathBuf};
 
+    use nvim_router::nvim_rs::Value;
+
     use std::sync::Arc;
     use tokio::sync::Mutex;
 
+    // Identifies a filer instance. Lua uses the handle of the tabpage showing it.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct InstanceId(i64);
+
+    impl From<i64> for InstanceId {
+        fn from(value: i64) -> Self {
+            Self(value)
+        }
+    }
+
+    impl From<InstanceId> for Value {
+        fn from(value: InstanceId) -> Self {
+            Value::from(value.0)
+        }
+    }
+
     #[derive(Debug, Clone, Default)]
     pub struct States {
+        pub instance: InstanceId,
         pub rendered_lines: Items,
         pub expanded_dir: ExpendedDir,
+        pub journal: Journal,
+        pub history: History,
+        pub register: Register,
+        pub view: ViewOpts,
+        pub session: Session,
+    }
+
+    impl States {
+        pub fn new(instance: InstanceId) -> Self {
+            Self {
+                instance,
+                rendered_lines: Items::new(instance),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FilterKind {
+        Hidden,
+        Ignored,
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct View {
+        pub filter: Filter,
+        pub sort: SortOrder,
+        // At most this many entries of a directory are rendered, followed by an item that
+        // renders the rest. Directories in `uncapped` are rendered entirely.
+        pub limit: Option<usize>,
+        pub uncapped: Arc<BTreeSet<PathBuf>>,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct ViewOpts(Arc<Mutex<View>>);
+
+    impl ViewOpts {
+        pub async fn get(&self) -> View {
+            let lock = self.0.lock().await;
+            lock.clone()
+        }
+
+        pub async fn set_filter(&self, filter: Filter) {
+            let mut lock = self.0.lock().await;
+            lock.filter = filter;
+        }
+
+        pub async fn toggle(&self, kind: FilterKind) -> View {
+            let mut lock = self.0.lock().await;
+            let filter = &mut lock.filter;
+            match kind {
+                FilterKind::Hidden => filter.hide_hidden = !filter.hide_hidden,
+                FilterKind::Ignored => filter.hide_ignored = !filter.hide_ignored,
+            }
+            lock.clone()
+        }
+
+        pub async fn set_sort(&self, sort: SortOrder) -> View {
+            let mut lock = self.0.lock().await;
+            lock.sort = sort;
+            lock.clone()
+        }
+
+        pub async fn set_limit(&self, limit: Option<usize>) {
+            let mut lock = self.0.lock().await;
+            lock.limit = limit;
+        }
+
+        pub async fn uncap(&self, dir: &Path) -> View {
+            let mut lock = self.0.lock().await;
+            Arc::make_mut(&mut lock.uncapped).insert(dir.to_path_buf());
+            lock.clone()
+        }
+    }
+
+    // Roots whose saved expanded directories have been restored in this session.
+    #[derive(Debug, Clone, Default)]
+    pub struct Session(Arc<Mutex<BTreeSet<PathBuf>>>);
+
+    impl Session {
+        // Returns false if the root has already been restored.
+        pub async fn begin(&self, root: &Path) -> bool {
+            let mut lock = self.0.lock().await;
+            lock.insert(root.to_path_buf())
+        }
+
+        pub async fn roots(&self) -> BTreeSet<PathBuf> {
+            let lock = self.0.lock().await;
+            lock.clone()
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum YankMode {
+        Copy,
+        Cut,
+    }
+
+    // Where a file is opened. `Window` holds a window id.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum OpenMode {
+        Current,
+        Split,
+        Vsplit,
+        Tab,
+        Window(i64),
+    }
+
+    impl OpenMode {
+        pub fn parse(mode: Option<&str>, win: Option<i64>) -> Self {
+            match (mode, win) {
+                (Some("split"), _) => Self::Split,
+                (Some("vsplit"), _) => Self::Vsplit,
+                (Some("tab"), _) => Self::Tab,
+                (Some("window"), Some(win)) => Self::Window(win),
+                _ => Self::Current,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum LinkKind {
+        Symbolic,
+        Hard,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Yanked {
+        pub mode: YankMode,
+        pub paths: Vec<PathBuf>,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Register(Arc<Mutex<Option<Yanked>>>);
+
+    impl Register {
+        pub async fn set(&self, mode: YankMode, paths: Vec<PathBuf>) {
+            let mut lock = self.0.lock().await;
+            *lock = Some(Yanked { mode, paths });
+        }
+
+        pub async fn get(&self) -> Option<Yanked> {
+            let lock = self.0.lock().await;
+            lock.clone()
+        }
+
+        pub async fn clear(&self) {
+            let mut lock = self.0.lock().await;
+            *lock = None;
+        }
     }
 
+    // `auto` holds the directories expanded only to follow the current buffer. They are
+    // collapsed again when the buffer changes, unless the user has expanded them since.
     #[derive(Debug, Clone, Default)]
-    pub struct ExpendedDir(Arc<Mutex<BTreeSet<PathBuf>>>);
+    pub struct ExpendedDir {
+        dirs: Arc<Mutex<BTreeSet<PathBuf>>>,
+        auto: Arc<Mutex<BTreeSet<PathBuf>>>,
+        watcher: Watcher,
+    }
 
     impl ExpendedDir {
         pub async fn clone(&self) -> BTreeSet<PathBuf> {
-            let lock = self.0.lock().await;
+            let lock = self.dirs.lock().await;
             lock.clone()
         }
 
         pub async fn contains(&self, path: &Path) -> bool {
-            let lock = self.0.lock().await;
+            let lock = self.dirs.lock().await;
             lock.contains(path)
         }
 
         pub async fn insert(&self, path: PathBuf) {
-            let mut lock = self.0.lock().await;
+            let mut lock = self.dirs.lock().await;
+            self.auto.lock().await.remove(&path);
+            self.watcher.add(&path).await;
             lock.insert(path);
         }
 
         pub async fn remove(&self, path: &Path) -> bool {
-            let mut lock = self.0.lock().await;
+            let mut lock = self.dirs.lock().await;
+            self.auto.lock().await.remove(path);
+            self.watcher.remove(path).await;
             lock.remove(path)
         }
 
+        // Expanded directories except the automatic ones.
+        pub async fn manual(&self) -> BTreeSet<PathBuf> {
+            let lock = self.dirs.lock().await;
+            let auto = self.auto.lock().await;
+            lock.difference(&auto).cloned().collect()
+        }
+
+        pub async fn take_auto(&self) -> BTreeSet<PathBuf> {
+            let mut lock = self.auto.lock().await;
+            std::mem::take(&mut lock)
+        }
+
+        pub async fn extend_auto(&self, dirs: impl IntoIterator<Item = PathBuf>) {
+            let mut lock = self.auto.lock().await;
+            lock.extend(dirs);
+        }
+
+        pub async fn stop(&self) {
+            self.watcher.stop().await;
+        }
+
         pub fn lock(&self) -> ExpendedDirLock<'_> {
             ExpendedDirLock(self)
         }
+
+        pub async fn watch(&self, nvim: &Neovim<NvimWtr>, states: &RootStates) {
+            let lock = self.dirs.lock().await;
+            self.watcher.start(nvim, states, &lock).await;
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        pub(super) fn watcher(&self) -> &Watcher {
+            &self.watcher
+        }
     }
 
     pub struct ExpendedDirLock<'a>(&'a ExpendedDir);
@@ -100,8 +370,11 @@ pub mod states {
         where
             Func: for<'b> FnOnce(&'b mut BTreeSet<PathBuf>) -> T,
         {
-            let mut lock = self.0.0.lock().await;
-            f(&mut lock)
+            let mut lock = self.0.dirs.lock().await;
+            let old = lock.clone();
+            let ret = f(&mut lock);
+            self.0.watcher.update(&old, &lock).await;
+            ret
         }
     }
 }
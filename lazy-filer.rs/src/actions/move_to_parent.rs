@@ -1,8 +1,10 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 
-use super::{Action, DirArg, States};
+use super::{Action, DirArg, Error, States};
 
+use super::git_status;
+use super::history;
 use super::utils;
 
 pub struct MoveToParent {
@@ -13,23 +15,32 @@ pub struct MoveToParent {
 impl Action for MoveToParent {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let dir = self.dir.as_path();
         let Some(parent) = dir.parent() else {
             return Ok(());
         };
+        history::record(&self.nvim, states, parent).await?;
+
         states
             .actions
             .expanded_dir
             .insert(parent.to_path_buf())
             .await;
+        git_status::update(&self.nvim, states, parent);
         let expanded_dir = states.actions.expanded_dir.clone().await;
+        let view = states.actions.view.get().await;
 
         let target_dir = utils::get_entries(&states.root_file, parent).await;
         target_dir.update_with_readdir().await?;
 
         target_dir
-            .render_entire_buffer(&self.nvim, &states.actions.rendered_lines, &expanded_dir)
+            .render_entire_buffer(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &view,
+            )
             .await?;
 
         Ok(())
@@ -1,8 +1,12 @@
 use super::{NvimErr, NvimWtr};
-use nvim_router::nvim_rs::Neovim;
+use nvim_router::nvim_rs::{Neovim, Value};
 
-use super::{Action, DirArg, States};
+use super::{Action, DirArg, Error, Operation, States};
+use crate::session;
 
+use super::git_status;
+use super::history;
+use super::states::InstanceId;
 use super::utils;
 
 pub struct NewFiler {
@@ -13,35 +17,75 @@ pub struct NewFiler {
 impl Action for NewFiler {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let dir = self.dir.as_path();
 
+        history::record(&self.nvim, states, dir).await?;
+
+        if states.actions.session.begin(dir).await {
+            match session::load(dir) {
+                Ok(saved) => {
+                    states
+                        .actions
+                        .expanded_dir
+                        .lock()
+                        .then(|expanded_dir| expanded_dir.extend(saved))
+                        .await;
+                }
+                Err(e) => {
+                    let e = Error::io(Operation::LoadState, dir, e);
+                    e.notify(&self.nvim).await?;
+                }
+            }
+        }
+
         states.actions.expanded_dir.insert(dir.to_path_buf()).await;
+        states.actions.expanded_dir.watch(&self.nvim, states).await;
+        git_status::update(&self.nvim, states, dir);
         let expanded_dir = states.actions.expanded_dir.clone().await;
+        let view = states.actions.view.get().await;
 
         let target_dir = utils::get_entries(&states.root_file, dir).await;
-        target_dir.update_with_readdir().await?;
+        let show = || async {
+            target_dir
+                .render_entire_buffer(
+                    &self.nvim,
+                    &states.actions.rendered_lines,
+                    &expanded_dir,
+                    &view,
+                )
+                .await?;
+            open_filer_win(&self.nvim, states.actions.instance).await?;
 
-        target_dir
-            .render_entire_buffer(&self.nvim, &states.actions.rendered_lines, &expanded_dir)
-            .await?;
-        open_filer_win(&self.nvim).await?;
+            Ok(())
+        };
+
+        target_dir.update_with_readdir_or(show).await?;
+        show().await?;
 
         target_dir
             .update_with_readdir_recursive(&expanded_dir)
             .await?;
 
         target_dir
-            .render_entire_buffer(&self.nvim, &states.actions.rendered_lines, &expanded_dir)
+            .render_entire_buffer(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &view,
+            )
             .await?;
 
         Ok(())
     }
 }
 
-async fn open_filer_win(nvim: &Neovim<NvimWtr>) -> Result<(), NvimErr> {
-    nvim.exec_lua("require('lazy-filer.call_lua').open_filer_win()", vec![])
-        .await?;
+pub async fn open_filer_win(nvim: &Neovim<NvimWtr>, instance: InstanceId) -> Result<(), NvimErr> {
+    nvim.exec_lua(
+        "require('lazy-filer.call_lua').open_filer_win(...)",
+        vec![Value::from(instance)],
+    )
+    .await?;
 
     Ok(())
 }
@@ -3,17 +3,19 @@ use nvim_router::nvim_rs::Neovim;
 use nvim_router::nvim_rs::Value;
 
 use super::renderer::LineIdx;
-use super::{Action, States};
+use super::states::OpenMode;
+use super::{Action, Error, States};
 
 pub struct OpenFile {
     pub line_idx: LineIdx,
     pub nvim: Neovim<NvimWtr>,
+    pub mode: OpenMode,
 }
 
 impl Action for OpenFile {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let Some(path) = states
             .actions
             .rendered_lines
@@ -30,7 +32,7 @@ impl Action for OpenFile {
             return Ok(());
         };
 
-        open_file(&self.nvim, path).await?;
+        open_file(&self.nvim, path, self.mode).await?;
 
         Ok(())
     }
@@ -40,25 +42,55 @@ async fn fname_escape(nvim: &Neovim<NvimWtr>, path: Value) -> Result<Value, Nvim
     nvim.call_function("fnameescape", vec![path]).await
 }
 
-async fn new_edit(nvim: &Neovim<NvimWtr>, path: &str) -> Result<(), NvimErr> {
-    let cmd = format!("edit! {path}");
-    nvim.command(&cmd).await?;
-
-    Ok(())
+async fn goto_win(nvim: &Neovim<NvimWtr>, win: i64) -> Result<bool, NvimErr> {
+    let found = nvim
+        .call_function("win_gotoid", vec![Value::from(win)])
+        .await?;
+    Ok(found.as_i64() == Some(1))
 }
 
-pub async fn open_file(nvim: &Neovim<NvimWtr>, path: Value) -> Result<(), NvimErr> {
-    let path = fname_escape(nvim, path).await?;
-    let Some(path) = path.as_str() else {
-        return Ok(());
-    };
+// A window already showing the file in the current tabpage (or any tabpage, for a new tab).
+async fn find_win(nvim: &Neovim<NvimWtr>, path: Value, mode: OpenMode) -> Result<Value, NvimErr> {
+    nvim.exec_lua(
+        "return require('lazy-filer.call_lua').find_win(...)",
+        vec![path, Value::from(mode == OpenMode::Tab)],
+    )
+    .await
+}
 
+pub async fn open_file(nvim: &Neovim<NvimWtr>, path: Value, mode: OpenMode) -> Result<(), Error> {
     nvim.exec_lua(
         "require('lazy-filer.call_lua').focus_on_last_active_win()",
         vec![],
     )
     .await?;
-    new_edit(nvim, path).await?;
+
+    // Jumping to the file keeps its cursor and unsaved changes, where editing it again would not.
+    if !matches!(mode, OpenMode::Window(_))
+        && let Some(win) = find_win(nvim, path.clone(), mode).await?.as_i64()
+        && goto_win(nvim, win).await?
+    {
+        return Ok(());
+    }
+
+    let path = fname_escape(nvim, path).await?;
+    let Some(path) = path.as_str() else {
+        return Ok(());
+    };
+
+    let cmd = match mode {
+        OpenMode::Current => "edit",
+        OpenMode::Split => "split",
+        OpenMode::Vsplit => "vsplit",
+        OpenMode::Tab => "tabedit",
+        OpenMode::Window(win) => {
+            if !goto_win(nvim, win).await? {
+                return Err(Error::InvalidInput(format!("no window with id {win}")));
+            }
+            "edit"
+        }
+    };
+    nvim.command(&format!("{cmd} {path}")).await?;
 
     Ok(())
 }
@@ -1,37 +1,44 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 use nvim_router::nvim_rs::Value;
 
-use super::renderer::{Level, LineIdx};
-use super::{Action, States};
-use super::{expand_dir::expand_dir, open_file::open_file};
+use super::expand_dir::{expand_dir, expand_rest};
+use super::open_file::open_file;
+use super::renderer::{FileType, Level, LineIdx};
+use super::states::OpenMode;
+use super::{Action, Error, States};
 
 use std::path::PathBuf;
 
 pub struct OpenOrExpand {
     pub line_idx: LineIdx,
     pub nvim: Neovim<NvimWtr>,
+    // Directories are expanded in place whatever the mode.
+    pub mode: OpenMode,
 }
 
 #[derive(Debug)]
 enum Path {
     Regular(Value),
     Directory(Level, PathBuf),
+    More(PathBuf),
 }
 
 impl Action for OpenOrExpand {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let Some(path) = states
             .actions
             .rendered_lines
             .get(self.line_idx)
-            .and_then(|item| {
+            .and_then_placeholder(|item| {
                 if item.metadata.is_regular() {
                     item.path.to_str().map(Value::from).map(Path::Regular)
                 } else if item.metadata.is_dir() {
                     Some(Path::Directory(item.level, item.path.to_path_buf()))
+                } else if let FileType::More(_) = item.metadata.file_type {
+                    Some(Path::More(item.path.to_path_buf()))
                 } else {
                     None
                 }
@@ -42,10 +49,11 @@ impl Action for OpenOrExpand {
         };
 
         match path {
-            Path::Regular(path) => open_file(&self.nvim, path).await?,
+            Path::Regular(path) => open_file(&self.nvim, path, self.mode).await?,
             Path::Directory(level, path) => {
                 expand_dir(self.line_idx, &self.nvim, level, &path, states).await?
             }
+            Path::More(path) => expand_rest(&self.nvim, &path, states).await?,
         }
 
         Ok(())
@@ -0,0 +1,175 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::file_ops::{self, FileOp};
+use super::journal::Mutation;
+use super::renderer::LineIdx;
+use super::states::{YankMode, Yanked};
+use super::utils;
+use super::{Action, Error, Operation, States};
+use crate::fs::File;
+
+use std::io::{Error as IoErr, ErrorKind};
+use std::path::Path;
+
+pub struct PasteEntry {
+    pub line_idx: LineIdx,
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for PasteEntry {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some(Yanked { mode, paths }) = states.actions.register.get().await else {
+            return Ok(());
+        };
+
+        let Some(dest_dir) = states
+            .actions
+            .rendered_lines
+            .get(self.line_idx)
+            .and_then(|item| {
+                if item.metadata.is_dir() {
+                    Some(item.path.to_path_buf())
+                } else {
+                    item.path.parent().map(Path::to_path_buf)
+                }
+            })
+            .await
+        else {
+            return Ok(());
+        };
+
+        for src in &paths {
+            paste(&self.nvim, states, mode, src, &dest_dir).await?;
+        }
+
+        if mode == YankMode::Cut {
+            states.actions.register.clear().await;
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn paste(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    mode: YankMode,
+    src: &Path,
+    dest_dir: &Path,
+) -> Result<(), Error> {
+    let (Some(fname), Some(src_parent)) = (src.file_name(), src.parent()) else {
+        return Ok(());
+    };
+
+    // A copy pasted next to its source, or onto a taken name, is given a name of its own.
+    let fname = if mode == YankMode::Copy && dest_dir.join(fname).symlink_metadata().is_ok() {
+        utils::free_name(dest_dir, fname, "copy", src.is_dir())
+    } else {
+        fname.to_os_string()
+    };
+
+    let dst = dest_dir.join(&fname);
+    if mode == YankMode::Cut && dst == src {
+        return Ok(());
+    }
+
+    let op = match mode {
+        YankMode::Copy => Operation::Copy { to: dst.clone() },
+        YankMode::Cut => Operation::Rename { to: dst.clone() },
+    };
+
+    if dst.symlink_metadata().is_ok() {
+        return Err(Error::already_exists(op, src));
+    }
+    if !src.is_symlink() && dest_dir.starts_with(src) {
+        return Err(Error::io(op, src, ErrorKind::InvalidInput.into()));
+    }
+
+    let ops = match mode {
+        YankMode::Copy => [FileOp::copy(src, &dst)],
+        YankMode::Cut => [FileOp::rename(src, &dst)],
+    };
+    file_ops::will(nvim, &ops).await?;
+
+    let file = match mode {
+        YankMode::Copy => {
+            utils::copy_all(src, &dst).map_err(|e| Error::io(op.clone(), src, e))?;
+            let file = File::read_from_path(&dst).map_err(|e| Error::io(op, src, e))?;
+
+            let mutation = Mutation::Copy {
+                from: src.to_path_buf(),
+                to: dst.clone(),
+                trashed: None,
+            };
+            states.actions.journal.record(mutation).await;
+            file_ops::did(nvim, &ops).await;
+
+            file
+        }
+        YankMode::Cut => {
+            move_all(src, &dst).map_err(|e| Error::io(op.clone(), src, e))?;
+
+            let src_dir = utils::get_entries(&states.root_file, src_parent).await;
+            let file = match src_dir.remove(src).await {
+                Some(file) => file,
+                None => File::read_from_path(&dst).map_err(|e| Error::io(op, src, e))?,
+            };
+
+            states
+                .actions
+                .expanded_dir
+                .lock()
+                .then(|expanded_dir| expanded_dir.retain(|path| !path.starts_with(src)))
+                .await;
+
+            states
+                .actions
+                .rendered_lines
+                .edit(nvim)
+                .remove_range(|lines| utils::find_in_dir(src, lines))
+                .await?;
+
+            let mutation = Mutation::Rename {
+                from: src.to_path_buf(),
+                to: dst.clone(),
+            };
+            states.actions.journal.record(mutation).await;
+            file_ops::did(nvim, &ops).await;
+
+            file
+        }
+    };
+
+    let target_dir = utils::get_entries(&states.root_file, dest_dir).await;
+    target_dir.insert(&fname, file.clone()).await;
+
+    let lines = &states.actions.rendered_lines;
+    if states.actions.expanded_dir.contains(dest_dir).await {
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+        let view = states.actions.view.get().await;
+        target_dir
+            .render_new_child(nvim, lines, &expanded_dir, &view, &dst, &file)
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn move_all(src: &Path, dst: &Path) -> Result<(), IoErr> {
+    match std::fs::rename(src, dst) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            utils::copy_all(src, dst)?;
+
+            let meta = src.symlink_metadata()?;
+            if meta.is_dir() {
+                std::fs::remove_dir_all(src)
+            } else {
+                std::fs::remove_file(src)
+            }
+        }
+        res => res,
+    }
+}
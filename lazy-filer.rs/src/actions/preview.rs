@@ -0,0 +1,165 @@
+use super::{NvimErr, NvimWtr};
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::renderer::LineIdx;
+use super::states::InstanceId;
+use super::{Action, Error, States};
+use crate::columns;
+use crate::preview::{self, Content, DirSummary, Limits};
+
+use std::io::{Error as IoError, ErrorKind};
+use std::time::Duration;
+
+// A preview of a slow filesystem says so, rather than never coming.
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Neovim does not wait for the preview: it is sent back with the line it was asked for, so
+// that Lua can drop it if the cursor has moved on since.
+pub struct Preview {
+    pub line_idx: LineIdx,
+    pub nvim: Neovim<NvimWtr>,
+    pub limits: Limits,
+}
+
+impl Action for Preview {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some(path) = states
+            .actions
+            .rendered_lines
+            .get(self.line_idx)
+            .and_then(|item| Some(item.path.to_path_buf()))
+            .await
+        else {
+            return Ok(());
+        };
+
+        let task = {
+            let path = path.clone();
+            let limits = self.limits;
+            tokio::task::spawn_blocking(move || preview::read(&path, limits))
+        };
+
+        let res = match tokio::time::timeout(PREVIEW_TIMEOUT, task).await {
+            Ok(Ok(res)) => res,
+            Ok(Err(e)) => Err(IoError::other(e)),
+            Err(_) => Err(IoError::from(ErrorKind::TimedOut)),
+        };
+
+        // Failures are shown in the preview itself, rather than notified on every cursor move.
+        let mut inner = match res {
+            Ok(content) => content_to_val(&content),
+            Err(e) if e.kind() == ErrorKind::TimedOut => vec![kind("timed_out")],
+            Err(e) => vec![
+                kind("error"),
+                (Value::from("message"), Value::from(e.to_string())),
+            ],
+        };
+        inner.push((
+            Value::from("path"),
+            Value::from(path.to_string_lossy().into_owned()),
+        ));
+
+        let instance = states.actions.instance;
+        show_preview(&self.nvim, instance, self.line_idx, Value::Map(inner)).await?;
+
+        Ok(())
+    }
+}
+
+async fn show_preview(
+    nvim: &Neovim<NvimWtr>,
+    instance: InstanceId,
+    line_idx: LineIdx,
+    preview: Value,
+) -> Result<(), NvimErr> {
+    nvim.exec_lua(
+        "require('lazy-filer.call_lua').show_preview(...)",
+        vec![
+            Value::from(instance),
+            Value::from(line_idx.to_num()),
+            preview,
+        ],
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn kind(kind: &str) -> (Value, Value) {
+    (Value::from("kind"), Value::from(kind))
+}
+
+fn content_to_val(content: &Content) -> Vec<(Value, Value)> {
+    match content {
+        Content::Text { lines, truncated } => vec![
+            kind("text"),
+            (
+                Value::from("lines"),
+                Value::Array(
+                    lines
+                        .iter()
+                        .map(|line| Value::from(line.as_str()))
+                        .collect(),
+                ),
+            ),
+            (Value::from("truncated"), Value::from(*truncated)),
+        ],
+        Content::Binary { size } => vec![
+            kind("binary"),
+            (Value::from("size"), Value::from(*size)),
+            (
+                Value::from("size_human"),
+                Value::from(columns::human_size(*size)),
+            ),
+        ],
+        Content::Directory(summary) => dir_to_val(summary),
+        Content::Link { to, target } => {
+            let mut inner = vec![
+                kind("link"),
+                (
+                    Value::from("to"),
+                    Value::from(to.to_string_lossy().into_owned()),
+                ),
+            ];
+            match target {
+                Some(target) => {
+                    inner.push((Value::from("target"), Value::Map(content_to_val(target))));
+                }
+                None => inner.push((Value::from("broken"), Value::from(true))),
+            }
+            inner
+        }
+        Content::Other { mode } => vec![
+            kind("other"),
+            (
+                Value::from("mode_str"),
+                Value::from(columns::mode_string(*mode)),
+            ),
+        ],
+    }
+}
+
+fn dir_to_val(summary: &DirSummary) -> Vec<(Value, Value)> {
+    let names = summary
+        .names
+        .iter()
+        .map(|name| Value::from(name.to_string_lossy().into_owned()))
+        .collect();
+
+    vec![
+        kind("directory"),
+        (Value::from("dirs"), Value::from(summary.dirs)),
+        (Value::from("files"), Value::from(summary.files)),
+        (Value::from("links"), Value::from(summary.links)),
+        (Value::from("others"), Value::from(summary.others)),
+        (Value::from("total_size"), Value::from(summary.total_size)),
+        (
+            Value::from("total_size_human"),
+            Value::from(columns::human_size(summary.total_size)),
+        ),
+        (Value::from("names"), Value::Array(names)),
+        (Value::from("truncated"), Value::from(summary.truncated)),
+    ]
+}
@@ -0,0 +1,17 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::journal::{self, Direction};
+use super::{Action, Error, States};
+
+pub struct Redo {
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for Redo {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        journal::replay(&self.nvim, states, Direction::Redo).await
+    }
+}
@@ -0,0 +1,23 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::{Action, Error, States};
+
+pub struct Redraw {
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for Redraw {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        states
+            .actions
+            .rendered_lines
+            .edit(&self.nvim)
+            .redraw_all()
+            .await?;
+
+        Ok(())
+    }
+}
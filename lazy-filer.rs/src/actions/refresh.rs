@@ -1,10 +1,16 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 
-use super::{Action, DirArg, States};
+use super::{Action, DirArg, Error, States};
+use crate::git;
 
+use super::git_status;
+use super::history;
 use super::utils;
 
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
 pub struct Refresh {
     pub nvim: Neovim<NvimWtr>,
     pub dir: DirArg,
@@ -13,11 +19,16 @@ pub struct Refresh {
 impl Action for Refresh {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let dir = self.dir.as_path();
 
+        history::record(&self.nvim, states, dir).await?;
+
         states.actions.expanded_dir.insert(dir.to_path_buf()).await;
+        states.actions.expanded_dir.watch(&self.nvim, states).await;
+        git_status::update(&self.nvim, states, dir);
         let expanded_dir = states.actions.expanded_dir.clone().await;
+        let view = states.actions.view.get().await;
 
         let target_dir = utils::get_entries(&states.root_file, dir).await;
         target_dir.update_with_readdir().await?;
@@ -27,9 +38,88 @@ impl Action for Refresh {
             .await?;
 
         target_dir
-            .render_entire_buffer(&self.nvim, &states.actions.rendered_lines, &expanded_dir)
+            .render_entire_buffer(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &view,
+            )
             .await?;
 
         Ok(())
     }
 }
+
+// Re-reads the directories only, e.g. those a watcher event came for, and renders their
+// children again.
+pub async fn refresh_dirs(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    dirs: &BTreeSet<PathBuf>,
+) -> Result<(), Error> {
+    refresh(nvim, states, dirs, false).await
+}
+
+// Re-reads the expanded directories below them as well, for changes that reach into them: a
+// recursive chmod, or a directory moved along with its expanded descendants.
+pub async fn refresh_trees(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    dirs: &BTreeSet<PathBuf>,
+) -> Result<(), Error> {
+    refresh(nvim, states, dirs, true).await
+}
+
+async fn refresh(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    dirs: &BTreeSet<PathBuf>,
+    recursive: bool,
+) -> Result<(), Error> {
+    let expanded_dir = states.actions.expanded_dir.clone().await;
+    let view = states.actions.view.get().await;
+
+    // A directory that has gone away is dropped by the readdir of its parent. Looking it up
+    // here would put it back into the cache.
+    let dirs: Vec<&Path> = dirs
+        .iter()
+        .map(|dir| &**dir)
+        .filter(|dir| dir.is_dir())
+        .collect();
+
+    for &dir in &dirs {
+        let target_dir = utils::get_entries(&states.root_file, dir).await;
+        target_dir.update_with_readdir().await.ok();
+        if recursive {
+            target_dir
+                .update_with_readdir_recursive(&expanded_dir)
+                .await
+                .ok();
+        }
+    }
+
+    // Paths are sorted, so every ancestor is rendered before its descendants.
+    let mut rendered: Vec<&Path> = Vec::new();
+    for dir in dirs {
+        if rendered.iter().any(|anc| dir.starts_with(anc)) {
+            continue;
+        }
+
+        let target_dir = utils::get_entries(&states.root_file, dir).await;
+        target_dir
+            .render_children(nvim, &states.actions.rendered_lines, &expanded_dir, &view)
+            .await?;
+
+        rendered.push(dir);
+    }
+
+    let repos: BTreeSet<PathBuf> = rendered
+        .iter()
+        .filter_map(|dir| git::repo_root(dir))
+        .collect();
+    for repo in repos {
+        git_status::update(nvim, states, &repo);
+    }
+
+    Ok(())
+}
@@ -1,11 +1,17 @@
-use super::{NvimErr, NvimWtr};
+use super::NvimWtr;
 use nvim_router::nvim_rs::Neovim;
 
+use super::file_ops::{self, FileOp};
+use super::journal::Mutation;
+use super::paste_entry;
 use super::renderer::{Level, LineIdx};
+use super::states::YankMode;
 use super::utils;
-use super::{Action, DirArg, States};
+use super::{Action, DirArg, Error, Operation, States};
 
+use std::collections::BTreeSet;
 use std::ffi::OsStr;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
 pub struct RenameEntry {
@@ -18,7 +24,7 @@ pub struct RenameEntry {
 impl Action for RenameEntry {
     type Resp = ();
 
-    async fn run(&self, states: &States) -> Result<Self::Resp, NvimErr> {
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
         let Some(old_path) = states
             .actions
             .rendered_lines
@@ -29,21 +35,45 @@ impl Action for RenameEntry {
             return Ok(());
         };
 
+        let selection = states.actions.rendered_lines.selection().get().await;
+        if !selection.is_empty() {
+            return self.move_selected(states, &old_path, selection).await;
+        }
+
         let Some(old_parent) = old_path.parent() else {
             return Ok(());
         };
 
         let new_path = resolve(&old_path, self.path.as_ref());
+        if new_path == old_path {
+            return Ok(());
+        }
         let Some(new_parent) = new_path.parent() else {
             return Ok(());
         };
 
         let new_fname = safe_fname(&new_path);
 
-        if std::fs::rename(&old_path, &new_path).is_err() {
-            return Ok(());
+        let op = Operation::Rename {
+            to: new_path.clone(),
+        };
+        if new_path.symlink_metadata().is_ok() {
+            return Err(Error::already_exists(op, &old_path));
+        }
+
+        let ops = [FileOp::rename(&old_path, &new_path)];
+        file_ops::will(&self.nvim, &ops).await?;
+        if let Err(e) = std::fs::rename(&old_path, &new_path) {
+            return Err(Error::io(op, &old_path, e));
         }
 
+        let mutation = Mutation::Rename {
+            from: old_path.clone(),
+            to: new_path.clone(),
+        };
+        states.actions.journal.record(mutation).await;
+        file_ops::did(&self.nvim, &ops).await;
+
         let src_dir = utils::get_entries(&states.root_file, old_parent).await;
         let Some(file) = src_dir.remove(&old_path).await else {
             return Ok(());
@@ -104,7 +134,7 @@ impl Action for RenameEntry {
             let target_dir = utils::get_entries(&states.root_file, &ancestor).await;
 
             let stream = target_dir
-                .flatten(level)
+                .flatten(level, &states.actions.view.get().await)
                 .filter(|path| expanded_dir.contains(path))
                 .await;
 
@@ -134,38 +164,69 @@ impl Action for RenameEntry {
     }
 }
 
+impl RenameEntry {
+    // With a selection, the input names the directory every selected entry is moved into.
+    async fn move_selected(
+        &self,
+        states: &States,
+        old_path: &Path,
+        selection: BTreeSet<PathBuf>,
+    ) -> Result<(), Error> {
+        let dest_dir = concat(old_path, self.path.as_ref());
+        if !dest_dir.is_dir() {
+            let op = Operation::Rename { to: dest_dir };
+            return Err(Error::io(op, old_path, ErrorKind::NotADirectory.into()));
+        }
+
+        states.actions.rendered_lines.selection().take().await;
+
+        let mut moved: Vec<&PathBuf> = Vec::new();
+        for src in &selection {
+            if moved.iter().any(|path| src.starts_with(path)) {
+                continue;
+            }
+            paste_entry::paste(&self.nvim, states, YankMode::Cut, src, &dest_dir).await?;
+            moved.push(src);
+        }
+
+        Ok(())
+    }
+}
+
 fn safe_fname(path: &Path) -> &OsStr {
     path.file_name().unwrap_or_default()
 }
 
-fn resolve(old_path: &Path, new_path: &Path) -> PathBuf {
-    fn concat(old_path: &Path, new_path: &Path) -> PathBuf {
-        use std::path::Component;
+fn concat(old_path: &Path, new_path: &Path) -> PathBuf {
+    use std::path::Component;
 
-        if new_path.is_absolute() {
-            return new_path.to_path_buf();
-        }
+    if new_path.is_absolute() {
+        return new_path.to_path_buf();
+    }
 
-        let mut ret = old_path.to_path_buf();
-        ret.pop();
+    let mut ret = old_path.to_path_buf();
+    ret.pop();
 
-        for component in new_path.components() {
-            match component {
-                Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
-                Component::ParentDir => {
-                    ret.pop();
-                }
-                Component::Normal(fname) => {
-                    ret.push(fname);
-                }
+    for component in new_path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+            Component::ParentDir => {
+                ret.pop();
+            }
+            Component::Normal(fname) => {
+                ret.push(fname);
             }
         }
-
-        ret
     }
 
+    ret
+}
+
+fn resolve(old_path: &Path, new_path: &Path) -> PathBuf {
     fn append_fname(old_path: &Path, mut new_path: PathBuf) -> PathBuf {
-        if new_path.is_dir()
+        // A directory renamed to its own name is not moved into itself.
+        if new_path != old_path
+            && new_path.is_dir()
             && let Some(fname) = old_path.file_name()
         {
             new_path.push(fname);
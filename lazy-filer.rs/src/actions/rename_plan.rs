@@ -0,0 +1,198 @@
+use super::{Error, Operation};
+
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+// Orders the renames so that no step overwrites an entry that has yet to be moved. Cycles such
+// as a -> b, b -> a are broken by first moving one of the entries to a temporary name.
+pub fn plan(renames: Vec<Rename>) -> Result<Vec<Rename>, Error> {
+    let mut pending: Vec<Rename> = renames.into_iter().filter(|r| r.from != r.to).collect();
+
+    validate(&pending)?;
+
+    let mut reserved: BTreeSet<PathBuf> = pending
+        .iter()
+        .flat_map(|r| [r.from.clone(), r.to.clone()])
+        .collect();
+
+    let mut steps = Vec::with_capacity(pending.len());
+
+    while !pending.is_empty() {
+        let occupied: BTreeSet<&Path> = pending.iter().map(|r| &*r.from).collect();
+
+        if let Some(idx) = pending.iter().position(|r| !occupied.contains(&*r.to)) {
+            steps.push(pending.remove(idx));
+            continue;
+        }
+
+        // Every remaining destination is still taken by another source.
+        let rename = &mut pending[0];
+        let tmp = temp_path(&rename.from, &reserved);
+        reserved.insert(tmp.clone());
+
+        steps.push(Rename {
+            from: rename.from.clone(),
+            to: tmp.clone(),
+        });
+        rename.from = tmp;
+    }
+
+    Ok(steps)
+}
+
+fn validate(renames: &[Rename]) -> Result<(), Error> {
+    let sources: BTreeSet<&Path> = renames.iter().map(|r| &*r.from).collect();
+    let mut dests: BTreeSet<&Path> = BTreeSet::new();
+
+    for Rename { from, to } in renames {
+        let op = Operation::Rename { to: to.clone() };
+
+        if to.file_name().is_none() {
+            return Err(Error::io(op, from, ErrorKind::InvalidInput.into()));
+        }
+        if !dests.insert(to) {
+            return Err(Error::InvalidInput(format!(
+                "more than one entry is renamed to {}",
+                to.display()
+            )));
+        }
+        if to.starts_with(from) {
+            return Err(Error::io(op, from, ErrorKind::InvalidInput.into()));
+        }
+        // Paths under a directory that is itself renamed would be stale by the time they move.
+        if let Some(anc) = sources.iter().find(|&&src| {
+            src != from && (from.starts_with(src) || (to.starts_with(src) && to != src))
+        }) {
+            return Err(Error::InvalidInput(format!(
+                "cannot rename {} together with {}",
+                from.display(),
+                anc.display()
+            )));
+        }
+        if !sources.contains(&**to) && to.symlink_metadata().is_ok() {
+            return Err(Error::already_exists(op, from));
+        }
+    }
+
+    Ok(())
+}
+
+fn temp_path(path: &Path, reserved: &BTreeSet<PathBuf>) -> PathBuf {
+    let fname = path.file_name().unwrap_or_default();
+
+    for n in 0.. {
+        let mut tmp = OsString::from(".");
+        tmp.push(fname);
+        tmp.push(format!(".lazy-filer-{n}"));
+
+        let tmp = path.with_file_name(tmp);
+        if !reserved.contains(&tmp) && tmp.symlink_metadata().is_err() {
+            return tmp;
+        }
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    // Nothing exists under this directory, so every destination is free.
+    const ROOT: &str = "/nonexistent/lazy-filer-rename-plan";
+
+    fn rename(from: &str, to: &str) -> Rename {
+        Rename {
+            from: Path::new(ROOT).join(from),
+            to: Path::new(ROOT).join(to),
+        }
+    }
+
+    // Replays the steps on an in-memory tree, checking that none of them overwrites an entry.
+    fn check(renames: &[Rename]) -> Vec<Rename> {
+        let steps = plan(renames.to_vec()).unwrap();
+
+        let mut tree: BTreeMap<PathBuf, PathBuf> = renames
+            .iter()
+            .map(|r| (r.from.clone(), r.from.clone()))
+            .collect();
+        for step in &steps {
+            assert!(!tree.contains_key(&step.to), "overwrites {:?}", step.to);
+            let origin = tree.remove(&step.from).expect("moves a missing entry");
+            tree.insert(step.to.clone(), origin);
+        }
+
+        for r in renames {
+            assert_eq!(tree.get(&r.to), Some(&r.from));
+        }
+        assert_eq!(tree.len(), renames.len());
+
+        steps
+    }
+
+    #[test]
+    fn chain() {
+        let steps = check(&[rename("a", "b"), rename("b", "c")]);
+        assert_eq!(steps, vec![rename("b", "c"), rename("a", "b")]);
+    }
+
+    #[test]
+    fn swap() {
+        let steps = check(&[rename("a", "b"), rename("b", "a")]);
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0], rename("a", ".a.lazy-filer-0"));
+    }
+
+    #[test]
+    fn three_cycle() {
+        let steps = check(&[rename("a", "b"), rename("b", "c"), rename("c", "a")]);
+        assert_eq!(steps.len(), 4);
+    }
+
+    #[test]
+    fn unchanged_names_are_skipped() {
+        let steps = check(&[rename("a", "a"), rename("b", "c")]);
+        assert_eq!(steps, vec![rename("b", "c")]);
+    }
+
+    #[test]
+    fn duplicate_targets() {
+        let res = plan(vec![rename("a", "c"), rename("b", "c")]);
+        assert!(matches!(res, Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn collision_with_untouched_file() {
+        let dir =
+            std::env::temp_dir().join(format!("lazy-filer-rename-plan-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("taken"), "").unwrap();
+
+        let res = plan(vec![Rename {
+            from: dir.join("a"),
+            to: dir.join("taken"),
+        }]);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            res,
+            Err(Error::Io { source, .. }) if source.kind() == ErrorKind::AlreadyExists
+        ));
+    }
+
+    #[test]
+    fn entry_under_renamed_directory() {
+        let res = plan(vec![rename("d", "e"), rename("d/f", "g")]);
+        assert!(matches!(res, Err(Error::InvalidInput(_))));
+    }
+}
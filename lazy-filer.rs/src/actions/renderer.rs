@@ -2,12 +2,16 @@ use super::{NvimErr, NvimWtr};
 use nvim_router::nvim_rs::Neovim;
 use nvim_router::nvim_rs::Value;
 
-use crate::fs::Permissions;
+use super::git_status::{GitCache, Repos};
+use super::states::InstanceId;
+use crate::columns::{self, Ids, Names};
+use crate::fs::{Permissions, Stat};
 
 use std::ops::Add;
 use std::ops::RangeBounds;
 
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::sync::MutexGuard;
@@ -41,11 +45,17 @@ pub enum FileType {
     LinkDirectory,
     LinkOther,
     Other,
+    // Placeholders for the entries of a directory being read, or too slow to read.
+    Loading,
+    TimedOut,
+    // Stands in for this many entries beyond the limit of a directory.
+    More(usize),
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Metadata {
     pub perm: Permissions,
+    pub stat: Option<Stat>,
     pub file_type: FileType,
 }
 impl Metadata {
@@ -60,6 +70,15 @@ impl Metadata {
         )
     }
 
+    pub fn placeholder(self) -> Option<&'static str> {
+        match self.file_type {
+            FileType::Loading => Some("loading"),
+            FileType::TimedOut => Some("timed_out"),
+            FileType::More(_) => Some("more"),
+            _ => None,
+        }
+    }
+
     pub fn is_link(self) -> bool {
         matches!(
             self.file_type,
@@ -76,11 +95,100 @@ pub struct Item {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct Items(Arc<Mutex<Vec<Item>>>);
+pub struct Items {
+    instance: InstanceId,
+    lines: Arc<Mutex<Vec<Item>>>,
+    selection: Selection,
+    git: GitCache,
+    time_format: TimeFormat,
+    current: Current,
+    names: NameCache,
+}
 
 impl Items {
+    pub fn new(instance: InstanceId) -> Self {
+        Self {
+            instance,
+            ..Default::default()
+        }
+    }
+
     async fn lock(&self) -> MutexGuard<'_, Vec<Item>> {
-        self.0.lock().await
+        self.lines.lock().await
+    }
+
+    async fn to_lua<'l, L>(&self, items: L) -> BufLines
+    where
+        L: IntoIterator<Item = &'l Item>,
+    {
+        let selected = self.selection.0.lock().await;
+        let git = self.git.lock().await;
+        let time_format = self.time_format.0.lock().await;
+        let current = self.current.0.lock().await;
+        let names = self.names.0.lock().await;
+        items_to_lua(
+            items,
+            &selected,
+            &git,
+            &time_format,
+            current.as_deref(),
+            &names,
+        )
+    }
+
+    // Looks up the owners and groups of the items that are not cached yet. No lock is held
+    // meanwhile, as the lookups may block.
+    async fn lookup_names<'l, L>(&self, items: L)
+    where
+        L: IntoIterator<Item = &'l Item>,
+    {
+        let ids = self.missing_names(items).await;
+        self.resolve_names(ids).await;
+    }
+
+    async fn lookup_rendered_names(&self) {
+        let ids = {
+            let lock = self.lock().await;
+            self.missing_names(lock.iter()).await
+        };
+        self.resolve_names(ids).await;
+    }
+
+    async fn missing_names<'l, L>(&self, items: L) -> Ids
+    where
+        L: IntoIterator<Item = &'l Item>,
+    {
+        let names = self.names.0.lock().await;
+        let ids = items
+            .into_iter()
+            .filter_map(|item| item.metadata.stat)
+            .map(|stat| (stat.uid, stat.gid));
+        names.missing(ids)
+    }
+
+    async fn resolve_names(&self, ids: Ids) {
+        if ids.is_empty() {
+            return;
+        }
+        if let Ok(found) = tokio::task::spawn_blocking(move || ids.lookup()).await {
+            self.names.0.lock().await.extend(found);
+        }
+    }
+
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    pub fn git(&self) -> &GitCache {
+        &self.git
+    }
+
+    pub fn time_format(&self) -> &TimeFormat {
+        &self.time_format
+    }
+
+    pub fn current(&self) -> &Current {
+        &self.current
     }
 
     pub fn edit<'n>(&self, nvim: &'n Neovim<NvimWtr>) -> Edit<'_, 'n> {
@@ -94,12 +202,38 @@ impl Items {
     pub fn iter(&self) -> ItemIter<'_> {
         ItemIter { inner: self }
     }
+
+    pub async fn position(&self, path: &Path) -> Option<usize> {
+        let lock = self.lock().await;
+        lock.iter().position(|item| item.path == path)
+    }
+
+    pub async fn paths(&self, start: LineIdx, end: LineIdx) -> Vec<PathBuf> {
+        let lock = self.lock().await;
+
+        let (Some(start), Some(end)) = (start.as_usize(lock.len()), end.as_usize(lock.len()))
+        else {
+            return Vec::new();
+        };
+        let (start, end) = (start.min(end), start.max(end));
+
+        lock.iter()
+            .skip(start)
+            .take(end - start + 1)
+            .filter(|item| item.level != Level::base() && item.metadata.placeholder().is_none())
+            .map(|item| item.path.to_path_buf())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct LineIdx(i64);
 
 impl LineIdx {
+    pub fn to_num(self) -> i64 {
+        self.0
+    }
+
     fn as_usize(self, len: usize) -> Option<usize> {
         let idx = self.0;
         if idx >= 0 {
@@ -131,13 +265,18 @@ pub struct Edit<'a, 'n> {
 }
 
 struct BufLines(Vec<Value>);
-fn items_to_lua<'l, L>(items: L) -> BufLines
+fn items_to_lua<'l, L>(
+    items: L,
+    selected: &BTreeSet<PathBuf>,
+    git: &Repos,
+    time_format: &str,
+    current: Option<&Path>,
+    names: &Names,
+) -> BufLines
 where
     L: IntoIterator<Item = &'l Item>,
 {
-    fn item_to_lua(item: &Item) -> Value {
-        use std::path::Path;
-
+    let item_to_lua = |item: &Item| -> Value {
         let fname = item.path.file_name().unwrap_or_default();
         let fname: &Path = fname.as_ref();
         let fname = fname.display().to_string();
@@ -156,8 +295,32 @@ where
             (Value::from("read"), Value::from(item.metadata.perm.read)),
             (Value::from("write"), Value::from(item.metadata.perm.write)),
             (Value::from("exec"), Value::from(item.metadata.perm.exec)),
+            (
+                Value::from("selected"),
+                Value::from(selected.contains(&item.path)),
+            ),
+            (
+                Value::from("current"),
+                Value::from(current == Some(&*item.path)),
+            ),
         ];
 
+        if let Some(placeholder) = item.metadata.placeholder() {
+            inner.push((Value::from("placeholder"), Value::from(placeholder)));
+            if let FileType::More(rest) = item.metadata.file_type {
+                inner.push((Value::from("rest"), Value::from(rest)));
+            }
+            return Value::Map(inner);
+        }
+
+        if let Some(stat) = item.metadata.stat {
+            stat_to_lua(&mut inner, &stat, time_format, names);
+        }
+
+        if let Some(status) = git.get(&item.path, item.metadata.is_dir()) {
+            inner.push((Value::from("git"), Value::from(status.as_str())));
+        }
+
         if item.metadata.is_link()
             && let Ok(target) = std::fs::read_link(&item.path)
         {
@@ -166,36 +329,130 @@ where
         }
 
         Value::Map(inner)
-    }
+    };
 
     BufLines(items.into_iter().map(item_to_lua).collect())
 }
 
+fn stat_to_lua(inner: &mut Vec<(Value, Value)>, stat: &Stat, time_format: &str, names: &Names) {
+    let time = |secs| Value::from(columns::format_time(secs, time_format));
+
+    inner.extend([
+        (Value::from("size"), Value::from(stat.size)),
+        (
+            Value::from("size_human"),
+            Value::from(columns::human_size(stat.size)),
+        ),
+        (Value::from("mtime"), time(stat.mtime)),
+        (Value::from("ctime"), time(stat.ctime)),
+        (Value::from("uid"), Value::from(stat.uid)),
+        (Value::from("gid"), Value::from(stat.gid)),
+        (Value::from("owner"), Value::from(names.user(stat.uid))),
+        (Value::from("group"), Value::from(names.group(stat.gid))),
+        (Value::from("mode"), Value::from(stat.mode)),
+        (
+            Value::from("mode_str"),
+            Value::from(columns::mode_string(stat.mode)),
+        ),
+        (Value::from("nlink"), Value::from(stat.nlink)),
+        (Value::from("ino"), Value::from(stat.ino)),
+    ]);
+
+    if let Some(btime) = stat.btime {
+        inner.push((Value::from("btime"), time(btime)));
+    }
+}
+
 async fn update_buf(
     nvim: &Neovim<NvimWtr>,
+    instance: InstanceId,
     start: i64,
     end: i64,
     BufLines(items): BufLines,
 ) -> Result<(), NvimErr> {
     nvim.exec_lua(
         "require('lazy-filer.call_lua').update_filer_buf(...)",
-        vec![Value::from(start), Value::from(end), Value::Array(items)],
+        vec![
+            Value::from(instance),
+            Value::from(start),
+            Value::from(end),
+            Value::Array(items),
+        ],
     )
     .await?;
 
     Ok(())
 }
 
+fn bounds(range: &impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    use std::ops::Bound;
+
+    let start = match range.start_bound() {
+        Bound::Included(&n) => n,
+        Bound::Excluded(&n) => n + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&n) => n + 1,
+        Bound::Excluded(&n) => n,
+        Bound::Unbounded => len,
+    };
+
+    (start, end.min(len))
+}
+
 impl Edit<'_, '_> {
+    pub async fn redraw<Func, Range>(self, range: Func) -> Result<(), NvimErr>
+    where
+        Func: for<'a> FnOnce(&'a [Item]) -> Range,
+        Range: RangeBounds<usize>,
+    {
+        self.inner.lookup_rendered_names().await;
+
+        let lock = self.inner.lock().await;
+
+        let range = range(&lock);
+        let (start, end) = bounds(&range, lock.len());
+        let start = start.min(end);
+
+        let items = self.inner.to_lua(&lock[start..end]).await;
+        drop(lock);
+
+        update_buf(
+            self.nvim,
+            self.inner.instance,
+            start as i64,
+            end as i64,
+            items,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    // Replaces every line of the buffer, which may have missed updates while it was hidden.
+    pub async fn redraw_all(self) -> Result<(), NvimErr> {
+        self.inner.lookup_rendered_names().await;
+
+        let lock = self.inner.lock().await;
+        let items = self.inner.to_lua(lock.iter()).await;
+        drop(lock);
+
+        update_buf(self.nvim, self.inner.instance, 0, -1, items).await?;
+
+        Ok(())
+    }
+
     pub async fn replace_all(self, lines: impl Stream<Item = Item>) -> Result<(), NvimErr> {
         let lines = lines.collect::<Vec<_>>().await;
-        let items = items_to_lua(&lines);
+        self.inner.lookup_names(&lines).await;
+        let items = self.inner.to_lua(&lines).await;
 
         let mut lock = self.inner.lock().await;
         *lock = lines;
         drop(lock);
 
-        update_buf(self.nvim, 0, -1, items).await?;
+        update_buf(self.nvim, self.inner.instance, 0, -1, items).await?;
 
         Ok(())
     }
@@ -206,36 +463,34 @@ impl Edit<'_, '_> {
         Func: for<'a> FnOnce(&'a [Item]) -> Range,
         Range: RangeBounds<usize>,
     {
-        use std::ops::Bound;
-
         let lines = lines.collect::<Vec<_>>().await;
-        let items = items_to_lua(&lines);
+        self.inner.lookup_names(&lines).await;
+        let items = self.inner.to_lua(&lines).await;
 
         let mut lock = self.inner.lock().await;
         let range = range(&lock);
 
-        let start = match range.start_bound() {
-            Bound::Included(&n) => n,
-            Bound::Excluded(&n) => n + 1,
-            Bound::Unbounded => 0,
-        };
-        let end = match range.end_bound() {
-            Bound::Included(&n) => n + 1,
-            Bound::Excluded(&n) => n,
-            Bound::Unbounded => lock.len(),
-        };
+        let (start, end) = bounds(&range, lock.len());
 
         lock.splice(start..end, lines);
         drop(lock);
 
-        update_buf(self.nvim, start as i64, end as i64, items).await?;
+        update_buf(
+            self.nvim,
+            self.inner.instance,
+            start as i64,
+            end as i64,
+            items,
+        )
+        .await?;
 
         Ok(())
     }
 
     pub async fn insert(self, lines: impl Stream<Item = Item>, at: LineIdx) -> Result<(), NvimErr> {
         let lines = lines.collect::<Vec<_>>().await;
-        let items = items_to_lua(&lines);
+        self.inner.lookup_names(&lines).await;
+        let items = self.inner.to_lua(&lines).await;
 
         let mut lock = self.inner.lock().await;
         if let Some(at) = at.as_usize(lock.len()) {
@@ -244,7 +499,7 @@ impl Edit<'_, '_> {
         drop(lock);
 
         let LineIdx(at) = at;
-        update_buf(self.nvim, at, at, items).await?;
+        update_buf(self.nvim, self.inner.instance, at, at, items).await?;
 
         Ok(())
     }
@@ -254,11 +509,12 @@ impl Edit<'_, '_> {
         item: Item,
         at: impl for<'a> FnOnce(&'a [Item]) -> usize,
     ) -> Result<(), NvimErr> {
+        self.inner.lookup_names([&item]).await;
         let mut lock = self.inner.lock().await;
 
         let at = at(&lock);
 
-        let items = items_to_lua([&item]);
+        let items = self.inner.to_lua([&item]).await;
 
         if at >= lock.len() {
             lock.push(item);
@@ -266,7 +522,7 @@ impl Edit<'_, '_> {
             lock.insert(at, item);
         }
         drop(lock);
-        update_buf(self.nvim, at as i64, at as i64, items).await?;
+        update_buf(self.nvim, self.inner.instance, at as i64, at as i64, items).await?;
 
         Ok(())
     }
@@ -280,7 +536,14 @@ impl Edit<'_, '_> {
         drop(lock);
 
         let LineIdx(at) = at;
-        update_buf(self.nvim, at, at + 1, items_to_lua([])).await?;
+        update_buf(
+            self.nvim,
+            self.inner.instance,
+            at,
+            at + 1,
+            BufLines(Vec::new()),
+        )
+        .await?;
 
         Ok(())
     }
@@ -290,39 +553,111 @@ impl Edit<'_, '_> {
         Func: for<'a> FnOnce(&'a [Item]) -> Range,
         Range: RangeBounds<usize>,
     {
-        use std::ops::Bound;
-
         let mut lock = self.inner.lock().await;
 
         let range = range(&lock);
 
-        let start = match range.start_bound() {
-            Bound::Included(&n) => n,
-            Bound::Excluded(&n) => n + 1,
-            Bound::Unbounded => 0,
-        };
-        let end = match range.end_bound() {
-            Bound::Included(&n) => n + 1,
-            Bound::Excluded(&n) => n,
-            Bound::Unbounded => lock.len(),
-        };
+        let (start, end) = bounds(&range, lock.len());
 
         lock.drain(start..end);
         drop(lock);
 
-        update_buf(self.nvim, start as i64, end as i64, items_to_lua([])).await?;
+        update_buf(
+            self.nvim,
+            self.inner.instance,
+            start as i64,
+            end as i64,
+            BufLines(Vec::new()),
+        )
+        .await?;
 
         Ok(())
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct TimeFormat(Arc<Mutex<String>>);
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(
+            columns::DEFAULT_TIME_FORMAT.to_string(),
+        )))
+    }
+}
+
+impl TimeFormat {
+    pub async fn set(&self, fmt: String) {
+        let mut lock = self.0.lock().await;
+        *lock = fmt;
+    }
+}
+
+// The file of the current buffer, while following it.
+#[derive(Debug, Clone, Default)]
+pub struct Current(Arc<Mutex<Option<PathBuf>>>);
+
+impl Current {
+    pub async fn replace(&self, path: Option<PathBuf>) -> Option<PathBuf> {
+        let mut lock = self.0.lock().await;
+        std::mem::replace(&mut lock, path)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct NameCache(Arc<Mutex<Names>>);
+
+#[derive(Debug, Clone, Default)]
+pub struct Selection(Arc<Mutex<BTreeSet<PathBuf>>>);
+
+impl Selection {
+    pub async fn get(&self) -> BTreeSet<PathBuf> {
+        let lock = self.0.lock().await;
+        lock.clone()
+    }
+
+    pub async fn take(&self) -> BTreeSet<PathBuf> {
+        let mut lock = self.0.lock().await;
+        std::mem::take(&mut lock)
+    }
+
+    pub async fn toggle(&self, path: &Path) {
+        let mut lock = self.0.lock().await;
+        if !lock.remove(path) {
+            lock.insert(path.to_path_buf());
+        }
+    }
+
+    pub async fn extend(&self, paths: impl IntoIterator<Item = PathBuf>) {
+        let mut lock = self.0.lock().await;
+        lock.extend(paths);
+    }
+}
+
 pub struct PathGetter<'a> {
     inner: &'a Items,
     idx: LineIdx,
 }
 
 impl PathGetter<'_> {
+    // Placeholder lines (loading, timed out, more entries) carry the path of their directory.
+    // They are skipped, so that no action takes them for the directory itself.
     pub async fn and_then<Func, T>(self, f: Func) -> Option<T>
+    where
+        Func: for<'p> FnOnce(&'p Item) -> Option<T>,
+    {
+        self.and_then_placeholder(|item| {
+            if item.metadata.placeholder().is_some() {
+                None
+            } else {
+                f(item)
+            }
+        })
+        .await
+    }
+
+    // Gives placeholder lines as well.
+    pub async fn and_then_placeholder<Func, T>(self, f: Func) -> Option<T>
     where
         Func: for<'p> FnOnce(&'p Item) -> Option<T>,
     {
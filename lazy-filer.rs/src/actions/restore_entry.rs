@@ -0,0 +1,53 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::file_ops::{self, FileOp};
+use super::utils;
+use super::{Action, Error, Operation, States};
+use crate::trash::TrashedFile;
+
+use std::path::PathBuf;
+
+pub struct RestoreEntry {
+    pub nvim: Neovim<NvimWtr>,
+    pub id: PathBuf,
+}
+
+impl Action for RestoreEntry {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let Some(file) = TrashedFile::from_info_path(&self.id) else {
+            return Ok(());
+        };
+
+        let original = match file.restore() {
+            Ok(original) => original,
+            Err(e) => return Err(Error::io(Operation::Restore, &file.file_path(), e)),
+        };
+        // The original path is only known once the file is back.
+        let ops = [FileOp::created(&original)];
+        file_ops::did(&self.nvim, &ops).await;
+
+        let Some(parent) = original.parent() else {
+            return Ok(());
+        };
+
+        let target_dir = utils::get_entries(&states.root_file, parent).await;
+        target_dir.update_with_readdir().await?;
+
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+
+        let view = states.actions.view.get().await;
+        target_dir
+            .render_children(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &view,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
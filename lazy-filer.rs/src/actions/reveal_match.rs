@@ -0,0 +1,109 @@
+use super::{NvimErr, NvimWtr};
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::states::InstanceId;
+use super::utils;
+use super::{Action, DirArg, Error, States};
+
+use std::path::{Path, PathBuf};
+
+pub struct RevealMatch {
+    pub nvim: Neovim<NvimWtr>,
+    pub dir: DirArg,
+    pub path: PathBuf,
+}
+
+impl Action for RevealMatch {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        reveal(&self.nvim, states, self.dir.as_path(), &self.path).await?;
+        Ok(())
+    }
+}
+
+// Expands every directory between the root and the path, then puts the cursor on its line.
+// Only the children of the outermost directory that was collapsed are rendered again, unless
+// the path is beyond the entry limit of a directory, which is then rendered entirely.
+// Returns the index of the line, or None if the path is not rendered (e.g. it is hidden, or
+// does not exist, as the file of a new buffer not written yet).
+pub async fn reveal(
+    nvim: &Neovim<NvimWtr>,
+    states: &States,
+    root: &Path,
+    path: &Path,
+) -> Result<Option<usize>, Error> {
+    if !path.starts_with(root) || path.symlink_metadata().is_err() {
+        return Ok(None);
+    }
+
+    let ancestors: Vec<PathBuf> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root) && dir.is_dir())
+        .map(Path::to_path_buf)
+        .collect();
+
+    let collapsed = states
+        .actions
+        .expanded_dir
+        .lock()
+        .then(|expanded_dir| {
+            let mut outermost = None;
+            for dir in ancestors.iter().rev() {
+                if expanded_dir.insert(dir.clone()) && outermost.is_none() {
+                    outermost = Some(dir.clone());
+                }
+            }
+            outermost
+        })
+        .await;
+
+    for dir in ancestors.iter().rev() {
+        let target_dir = utils::get_entries(&states.root_file, dir).await;
+        target_dir.update_with_readdir().await?;
+    }
+
+    let expanded_dir = states.actions.expanded_dir.clone().await;
+    let mut view = states.actions.view.get().await;
+    let lines = &states.actions.rendered_lines;
+
+    if let Some(dir) = collapsed {
+        let target_dir = utils::get_entries(&states.root_file, &dir).await;
+        target_dir
+            .render_children(nvim, lines, &expanded_dir, &view)
+            .await?;
+    }
+
+    if lines.position(path).await.is_none() {
+        for dir in &ancestors {
+            view = states.actions.view.uncap(dir).await;
+        }
+
+        let target_dir = utils::get_entries(&states.root_file, root).await;
+        target_dir
+            .render_entire_buffer(nvim, lines, &expanded_dir, &view)
+            .await?;
+    }
+
+    let idx = lines.position(path).await;
+    if let Some(idx) = idx {
+        set_cursor(nvim, states.actions.instance, idx).await?;
+    }
+
+    Ok(idx)
+}
+
+pub async fn set_cursor(
+    nvim: &Neovim<NvimWtr>,
+    instance: InstanceId,
+    idx: usize,
+) -> Result<(), NvimErr> {
+    nvim.exec_lua(
+        "require('lazy-filer.call_lua').set_cursor(...)",
+        vec![Value::from(instance), Value::from(idx + 1)],
+    )
+    .await?;
+
+    Ok(())
+}
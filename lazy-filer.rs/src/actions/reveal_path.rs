@@ -0,0 +1,73 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::{Neovim, Value};
+
+use super::git_status;
+use super::history;
+use super::new_filer::open_filer_win;
+use super::reveal_match::reveal;
+use super::utils;
+use super::{Action, DirArg, Error, States};
+
+use std::path::{Path, PathBuf};
+
+pub struct RevealPath {
+    pub nvim: Neovim<NvimWtr>,
+    pub dir: DirArg,
+    pub path: PathBuf,
+}
+
+impl Action for RevealPath {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        if !self.path.is_absolute() {
+            let msg = format!("not an absolute path: {}", self.path.display());
+            return Err(Error::InvalidInput(msg));
+        }
+        if self.path.symlink_metadata().is_err() {
+            let msg = format!("no such file or directory: {}", self.path.display());
+            return Err(Error::InvalidInput(msg));
+        }
+
+        // A path outside the displayed root is revealed from its parent directory instead.
+        let root = if self.path.starts_with(self.dir.as_path()) {
+            self.dir.as_path()
+        } else if let Some(parent) = self.path.parent() {
+            parent
+        } else {
+            return Ok(Value::Nil);
+        };
+
+        if states.actions.rendered_lines.position(root).await != Some(0) {
+            show_root(&self.nvim, states, root).await?;
+        }
+        open_filer_win(&self.nvim, states.actions.instance).await?;
+
+        let line = reveal(&self.nvim, states, root, &self.path).await?;
+
+        let root = root.to_string_lossy().into_owned();
+        let line = line.map_or(Value::Nil, |idx| Value::from(idx + 1));
+        Ok(Value::Map(vec![
+            (Value::from("root"), Value::from(root)),
+            (Value::from("line"), line),
+        ]))
+    }
+}
+
+async fn show_root(nvim: &Neovim<NvimWtr>, states: &States, root: &Path) -> Result<(), Error> {
+    history::record(nvim, states, root).await?;
+    states.actions.expanded_dir.insert(root.to_path_buf()).await;
+    states.actions.expanded_dir.watch(nvim, states).await;
+    git_status::update(nvim, states, root);
+    let expanded_dir = states.actions.expanded_dir.clone().await;
+    let view = states.actions.view.get().await;
+
+    let target_dir = utils::get_entries(&states.root_file, root).await;
+    target_dir.update_with_readdir().await?;
+
+    target_dir
+        .render_entire_buffer(nvim, &states.actions.rendered_lines, &expanded_dir, &view)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use nvim_router::nvim_rs::Value;
+
+use super::{Action, Error, Operation, States};
+use crate::session;
+
+pub struct SaveSession;
+
+impl Action for SaveSession {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        // Directories expanded to follow the current buffer are not restored.
+        let expanded_dir = states.actions.expanded_dir.manual().await;
+
+        for root in states.actions.session.roots().await {
+            session::save(&root, &expanded_dir)
+                .map_err(|e| Error::io(Operation::SaveState, &root, e))?;
+        }
+
+        Ok(Value::Nil)
+    }
+}
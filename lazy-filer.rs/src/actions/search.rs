@@ -0,0 +1,87 @@
+use nvim_router::nvim_rs::Value;
+
+use super::utils;
+use super::{Action, DirArg, Error, States};
+use crate::fs::File;
+use crate::fuzzy::Pattern;
+
+use std::path::PathBuf;
+
+const MAX_RESULTS: usize = 100;
+const MAX_READDIR: usize = 2000;
+
+pub struct Search {
+    pub dir: DirArg,
+    pub query: String,
+    // Unexpanded directories up to this depth are read on the way. With 0, only what is
+    // already cached is searched.
+    pub depth: usize,
+}
+
+impl Action for Search {
+    type Resp = Value;
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let pattern = Pattern::new(&self.query);
+        if pattern.is_empty() {
+            return Ok(Value::Array(Vec::new()));
+        }
+
+        let root = self.dir.as_path();
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+        let mut matcher = states.actions.view.get().await.filter.matcher();
+
+        let mut matches: Vec<(i64, PathBuf)> = Vec::new();
+        let mut budget = MAX_READDIR;
+        let mut stack = vec![(root.to_path_buf(), 0)];
+
+        while let Some((dir, depth)) = stack.pop() {
+            let target_dir = utils::get_entries(&states.root_file, &dir).await;
+
+            if depth > 0 && depth <= self.depth && budget > 0 && !expanded_dir.contains(&dir) {
+                budget -= 1;
+                target_dir.update_with_readdir().await.ok();
+            }
+
+            for (path, file) in target_dir.children().await {
+                // Symlinked directories are not followed, so that cycles cannot trap the walk.
+                let is_dir = matches!(file, File::Directory { .. });
+                if !matcher.is_visible(&path, is_dir) {
+                    continue;
+                }
+
+                if let Ok(rel) = path.strip_prefix(root)
+                    && let Some(rel) = rel.to_str()
+                    && let Some(score) = pattern.score(rel)
+                {
+                    matches.push((score, path.clone()));
+                }
+
+                if is_dir {
+                    stack.push((path, depth + 1));
+                }
+            }
+        }
+
+        matches.sort_unstable_by(|(a_score, a_path), (b_score, b_path)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| a_path.as_os_str().len().cmp(&b_path.as_os_str().len()))
+                .then_with(|| a_path.cmp(b_path))
+        });
+        matches.truncate(MAX_RESULTS);
+
+        let matches = matches
+            .into_iter()
+            .filter_map(|(score, path)| {
+                let path = path.to_str()?;
+                Some(Value::Map(vec![
+                    (Value::from("path"), Value::from(path)),
+                    (Value::from("score"), Value::from(score)),
+                ]))
+            })
+            .collect();
+
+        Ok(Value::Array(matches))
+    }
+}
@@ -0,0 +1,49 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::renderer::Level;
+use super::{Action, Error, States};
+
+use globset::Glob;
+
+pub struct SelectGlob {
+    pub pattern: String,
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for SelectGlob {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let matcher = match Glob::new(&self.pattern) {
+            Ok(glob) => glob.compile_matcher(),
+            Err(e) => return Err(Error::InvalidInput(e.to_string())),
+        };
+
+        let lines = &states.actions.rendered_lines;
+
+        let (_, paths) = lines
+            .iter()
+            .fold((None, Vec::new()), |(root, mut paths), item| {
+                if item.level == Level::base() {
+                    return (Some(item.path.to_path_buf()), paths);
+                }
+
+                if let Some(root) = &root
+                    && let Ok(path) = item.path.strip_prefix(root)
+                    && matcher.is_match(path)
+                {
+                    paths.push(item.path.to_path_buf());
+                }
+
+                (root, paths)
+            })
+            .await;
+
+        lines.selection().extend(paths).await;
+
+        lines.edit(&self.nvim).redraw(|_| ..).await?;
+
+        Ok(())
+    }
+}
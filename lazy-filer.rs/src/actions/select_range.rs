@@ -0,0 +1,26 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::renderer::LineIdx;
+use super::{Action, Error, States};
+
+pub struct SelectRange {
+    pub start: LineIdx,
+    pub end: LineIdx,
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for SelectRange {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let lines = &states.actions.rendered_lines;
+
+        let paths = lines.paths(self.start, self.end).await;
+        lines.selection().extend(paths).await;
+
+        lines.edit(&self.nvim).redraw(|_| ..).await?;
+
+        Ok(())
+    }
+}
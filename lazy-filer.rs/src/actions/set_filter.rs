@@ -0,0 +1,16 @@
+use super::{Action, Error, States};
+use crate::visibility::Filter;
+
+pub struct SetFilter {
+    pub filter: Filter,
+}
+
+impl Action for SetFilter {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        states.actions.view.set_filter(self.filter).await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use super::{Action, Error, States};
+
+pub struct SetLimit {
+    pub limit: Option<usize>,
+}
+
+impl Action for SetLimit {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        states.actions.view.set_limit(self.limit).await;
+
+        Ok(())
+    }
+}
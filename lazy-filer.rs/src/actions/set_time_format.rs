@@ -0,0 +1,26 @@
+use super::{Action, Error, States};
+use crate::columns;
+
+pub struct SetTimeFormat {
+    pub format: String,
+}
+
+impl Action for SetTimeFormat {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        if !columns::is_valid_time_format(&self.format) {
+            let msg = format!("invalid time format: {}", self.format);
+            return Err(Error::InvalidInput(msg));
+        }
+
+        states
+            .actions
+            .rendered_lines
+            .time_format()
+            .set(self.format.clone())
+            .await;
+
+        Ok(())
+    }
+}
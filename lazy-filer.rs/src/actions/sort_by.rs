@@ -0,0 +1,39 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::utils;
+use super::{Action, DirArg, Error, States};
+use crate::sort::SortOrder;
+
+pub struct SortBy {
+    pub nvim: Neovim<NvimWtr>,
+    // The displayed root to re-render, if there is one yet.
+    pub dir: Option<DirArg>,
+    pub sort: SortOrder,
+}
+
+impl Action for SortBy {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let view = states.actions.view.set_sort(self.sort).await;
+
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+
+        let target_dir = utils::get_entries(&states.root_file, dir.as_path()).await;
+        target_dir
+            .render_entire_buffer(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &view,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::states::FilterKind;
+use super::utils;
+use super::{Action, DirArg, Error, States};
+
+pub struct ToggleFilter {
+    pub nvim: Neovim<NvimWtr>,
+    pub dir: DirArg,
+    pub kind: FilterKind,
+}
+
+impl Action for ToggleFilter {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let view = states.actions.view.toggle(self.kind).await;
+        let expanded_dir = states.actions.expanded_dir.clone().await;
+
+        let target_dir = utils::get_entries(&states.root_file, self.dir.as_path()).await;
+        target_dir
+            .render_entire_buffer(
+                &self.nvim,
+                &states.actions.rendered_lines,
+                &expanded_dir,
+                &view,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
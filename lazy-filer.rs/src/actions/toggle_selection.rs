@@ -0,0 +1,44 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::renderer::{Level, LineIdx};
+use super::{Action, Error, States};
+
+pub struct ToggleSelection {
+    pub line_idx: LineIdx,
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for ToggleSelection {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let lines = &states.actions.rendered_lines;
+
+        let Some(path) = lines
+            .get(self.line_idx)
+            .and_then(|item| {
+                if item.level == Level::base() {
+                    None
+                } else {
+                    Some(item.path.to_path_buf())
+                }
+            })
+            .await
+        else {
+            return Ok(());
+        };
+
+        lines.selection().toggle(&path).await;
+
+        lines
+            .edit(&self.nvim)
+            .redraw(|lines| {
+                let idx = lines.iter().position(|item| item.path == path);
+                idx.map_or(0..0, |idx| idx..(idx + 1))
+            })
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::git_status;
+use super::{Action, DirArg, Error, States};
+
+pub struct UpdateGitStatus {
+    pub nvim: Neovim<NvimWtr>,
+    pub path: DirArg,
+}
+
+impl Action for UpdateGitStatus {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        git_status::update(&self.nvim, states, self.path.as_path());
+
+        Ok(())
+    }
+}
@@ -1,18 +1,30 @@
+use super::{Error, Operation};
 use super::{NvimErr, NvimWtr};
 use nvim_router::nvim_rs::Neovim;
 
 use super::renderer::{FileType, Item, Items, Level, Metadata};
-use crate::fs::{self, File, Permissions, RootFile};
+use super::states::View;
+use crate::bookmarks;
+use crate::fs::{self, File, Permissions, ReadStatus, RootFile, Stat};
+use crate::sort::{self, SortOrder};
+use crate::trash::{self, TrashedFile};
+use crate::visibility::Matcher;
 
 use std::collections::BTreeSet;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
+use std::future::Future;
 use std::io::Error as IoErr;
 use std::marker::PhantomData;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use futures::stream::Stream;
 
+// Reads slower than this show a placeholder while they go on.
+const LOADING_DELAY: Duration = Duration::from_millis(100);
+const READDIR_CONCURRENCY: usize = 8;
+
 pub struct Entries<'a> {
     entries: fs::Entries,
     dir: &'a Path,
@@ -23,64 +35,70 @@ pub async fn get_entries<'a>(root: &RootFile, dir: &'a Path) -> Entries<'a> {
     Entries { entries, dir }
 }
 
-async fn update_with_readdir(entries: &fs::Entries, dir: &Path) -> Result<(), NvimErr> {
-    use nvim_router::nvim_rs::error::CallError;
-
-    if let Err(e) = entries.update_with_readdir(dir).await {
-        let msg = e.to_string();
-        Err(Box::new(CallError::NeovimError(Some(0), msg)))
-    } else {
-        Ok(())
-    }
+async fn update_with_readdir(entries: &fs::Entries, dir: &Path) -> Result<(), Error> {
+    entries
+        .update_with_readdir(dir)
+        .await
+        .map_err(|e| Error::io(Operation::ReadDir, dir, e))
 }
 
 impl<'a> Entries<'a> {
-    pub async fn update_with_readdir(&self) -> Result<(), NvimErr> {
+    pub async fn update_with_readdir(&self) -> Result<(), Error> {
         update_with_readdir(&self.entries, self.dir).await
     }
 
-    pub async fn update_with_readdir_recursive(
-        &self,
-        expanded_dir: &BTreeSet<PathBuf>,
-    ) -> Result<(), NvimErr> {
-        let filter = |path: &Path| expanded_dir.contains(path);
-
-        let mut stack = {
-            let children = self.children().await;
-            let level = Level::base();
-            vec![(level.increment(), children.into_iter())]
-        };
-
-        while let Some(&mut (level, ref mut children)) = stack.last_mut() {
-            let Some((child_path, child)) = children.next() else {
-                stack.pop();
-                continue;
-            };
-
-            match child {
-                File::Directory { entries, perm: _ } => {
-                    update_with_readdir(&entries, &child_path).await?;
+    // Whether the cached entries can be rendered without reading the directory.
+    pub async fn is_read(&self) -> bool {
+        self.entries.children().await.status() == ReadStatus::Read
+    }
 
-                    if filter(&child_path) && level < Level::MAX {
-                        let mut children = Entries::children_in(&entries, &child_path).await;
-                        children.sort();
-                        stack.push((level.increment(), children.into_iter()));
-                    }
-                }
-                File::Link { to } => {
-                    let file = to.follow_link();
+    // Reads the directory. If it takes a while, `loading` is run once in the meantime, e.g. to
+    // render a placeholder; returns whether it was.
+    pub async fn update_with_readdir_or<Func, Fut>(&self, loading: Func) -> Result<bool, Error>
+    where
+        Func: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        let mut reading = std::pin::pin!(self.update_with_readdir());
 
-                    if let File::Directory { entries, perm: _ } = file {
-                        update_with_readdir(entries, &child_path).await?;
+        match tokio::time::timeout(LOADING_DELAY, &mut reading).await {
+            Ok(res) => res.map(|()| false),
+            Err(_) => {
+                loading().await?;
+                reading.await.map(|()| true)
+            }
+        }
+    }
 
-                        if filter(&child_path) && level < Level::MAX {
-                            let mut children = Entries::children_in(entries, &child_path).await;
-                            children.sort();
-                            stack.push((level.increment(), children.into_iter()));
-                        }
-                    }
+    // Reads the subdirectories level by level, each level concurrently.
+    pub async fn update_with_readdir_recursive(
+        &self,
+        expanded_dir: &BTreeSet<PathBuf>,
+    ) -> Result<(), Error> {
+        use futures::stream::{self, StreamExt as _, TryStreamExt as _};
+
+        let mut frontier: Vec<_> = self
+            .children()
+            .await
+            .subdirs(Level::base().increment())
+            .collect();
+
+        while !frontier.is_empty() {
+            let read: Vec<_> = stream::iter(frontier)
+                .map(|(level, path, entries)| async move {
+                    update_with_readdir(&entries, &path).await?;
+                    Ok::<_, Error>((level, path, entries))
+                })
+                .buffer_unordered(READDIR_CONCURRENCY)
+                .try_collect()
+                .await?;
+
+            frontier = Vec::new();
+            for (level, path, entries) in read {
+                if expanded_dir.contains(&path) && level < Level::MAX {
+                    let children = Entries::children_in(&entries, &path).await;
+                    frontier.extend(children.subdirs(level.increment()));
                 }
-                _ => {}
             }
         }
 
@@ -93,13 +111,41 @@ impl<'a> Entries<'a> {
     }
 
     pub async fn remove_fs(&self, path: &Path, recursive: bool) -> Result<(), IoErr> {
+        if recursive {
+            std::fs::remove_dir_all(path)?;
+        } else {
+            std::fs::remove_file(path)?;
+        }
+
+        self.forget(path, recursive).await;
+
+        Ok(())
+    }
+
+    pub async fn trash_fs(&self, path: &Path, recursive: bool) -> Result<TrashedFile, IoErr> {
+        let trashed = trash::trash(path)?;
+
+        self.forget(path, recursive).await;
+
+        Ok(trashed)
+    }
+
+    async fn forget(&self, path: &Path, recursive: bool) {
         async fn remove_recursive(file: File) {
             let mut stack = match file {
-                File::Directory { perm: _, entries } => vec![entries],
+                File::Directory {
+                    perm: _,
+                    stat: _,
+                    entries,
+                } => vec![entries],
                 File::Link { to } => {
                     let file = to.follow_link_owned();
                     match file {
-                        File::Directory { perm: _, entries } => vec![entries],
+                        File::Directory {
+                            perm: _,
+                            stat: _,
+                            entries,
+                        } => vec![entries],
                         _ => return,
                     }
                 }
@@ -110,11 +156,19 @@ impl<'a> Entries<'a> {
                 let files = entries.clear().await;
                 for file in files.into_values() {
                     match file {
-                        File::Directory { perm: _, entries } => stack.push(entries),
+                        File::Directory {
+                            perm: _,
+                            stat: _,
+                            entries,
+                        } => stack.push(entries),
                         File::Link { to } => {
                             let file = to.follow_link_owned();
                             match file {
-                                File::Directory { perm: _, entries } => stack.push(entries),
+                                File::Directory {
+                                    perm: _,
+                                    stat: _,
+                                    entries,
+                                } => stack.push(entries),
                                 _ => continue,
                             }
                         }
@@ -125,18 +179,13 @@ impl<'a> Entries<'a> {
         }
 
         let Some(fname) = path.file_name() else {
-            return Ok(());
+            return;
         };
 
-        let file = self.entries.remove(fname).await;
-
-        if recursive {
-            if let Some(file) = file {
-                remove_recursive(file).await;
-            }
-            std::fs::remove_dir_all(path)
-        } else {
-            std::fs::remove_file(path)
+        if let Some(file) = self.entries.remove(fname).await
+            && recursive
+        {
+            remove_recursive(file).await;
         }
     }
 
@@ -147,10 +196,11 @@ impl<'a> Entries<'a> {
             .await;
     }
 
-    pub fn flatten(&self, level: Level) -> FlattenEntries<'a, '_, Item> {
+    pub fn flatten(&self, level: Level, view: &View) -> FlattenEntries<'a, '_, Item> {
         FlattenEntries {
             inner: self,
             level,
+            view: view.clone(),
             marker: PhantomData,
         }
     }
@@ -161,7 +211,10 @@ impl<'a> Entries<'a> {
 
     async fn children_in(entries: &fs::Entries, dir: &Path) -> Children {
         let children = entries.children().await;
-        Children(children.iter().map(|(k, v)| (dir.join(k), v)).collect())
+        Children {
+            files: children.iter().map(|(k, v)| (dir.join(k), v)).collect(),
+            status: children.status(),
+        }
     }
 
     pub async fn render_entire_buffer(
@@ -169,11 +222,12 @@ impl<'a> Entries<'a> {
         nvim: &Neovim<NvimWtr>,
         lines: &Items,
         expanded_dir: &BTreeSet<PathBuf>,
+        view: &View,
     ) -> Result<(), NvimErr> {
         use futures::stream::{StreamExt as _, once};
 
         let stream = self
-            .flatten(Level::base())
+            .flatten(Level::base(), view)
             .filter(|path| expanded_dir.contains(path))
             .await;
 
@@ -183,6 +237,7 @@ impl<'a> Entries<'a> {
                 path: self.dir.to_path_buf(),
                 metadata: Metadata {
                     perm: Permissions::read_from_path(self.dir),
+                    stat: Some(Stat::read_from_path(self.dir)),
                     file_type: FileType::Directory,
                 },
             }
@@ -192,28 +247,101 @@ impl<'a> Entries<'a> {
 
         Ok(())
     }
+
+    pub async fn render_children(
+        &self,
+        nvim: &Neovim<NvimWtr>,
+        lines: &Items,
+        expanded_dir: &BTreeSet<PathBuf>,
+        view: &View,
+    ) -> Result<(), NvimErr> {
+        if !expanded_dir.contains(self.dir) {
+            return Ok(());
+        }
+
+        let Some(level) = find_level(lines, self.dir).await else {
+            return Ok(());
+        };
+
+        let stream = self
+            .flatten(level, view)
+            .filter(|path| expanded_dir.contains(path))
+            .await;
+
+        lines
+            .edit(nvim)
+            .replace_range(stream, |lines| {
+                let range = find_in_dir(self.dir, lines);
+                if range.start == range.end {
+                    range
+                } else {
+                    (range.start + 1)..(range.end)
+                }
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    // Shows `path`, a child just added to the cache. A directory capped at the limit is rendered
+    // again instead, so that its "… N more entries" line stays right.
+    pub async fn render_new_child(
+        &self,
+        nvim: &Neovim<NvimWtr>,
+        lines: &Items,
+        expanded_dir: &BTreeSet<PathBuf>,
+        view: &View,
+        path: &Path,
+        file: &File,
+    ) -> Result<(), NvimErr> {
+        if view.limit.is_some() && !view.uncapped.contains(self.dir) {
+            return self.render_children(nvim, lines, expanded_dir, view).await;
+        }
+
+        let Some(level) = find_level(lines, self.dir).await else {
+            return Ok(());
+        };
+        insert_sorted(nvim, lines, path, level.increment(), file, view).await
+    }
 }
 
-pub struct Children(Vec<(PathBuf, File)>);
+pub struct Children {
+    files: Vec<(PathBuf, File)>,
+    status: ReadStatus,
+}
 
 impl IntoIterator for Children {
     type Item = (PathBuf, File);
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.files.into_iter()
     }
 }
 
 impl Children {
-    fn sort(&mut self) {
-        self.0.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    fn subdirs(self, level: Level) -> impl Iterator<Item = (Level, PathBuf, fs::Entries)> {
+        self.files
+            .into_iter()
+            .filter_map(move |(path, file)| match file.follow_link() {
+                File::Directory {
+                    entries,
+                    perm: _,
+                    stat: _,
+                } => Some((level, path, entries.clone())),
+                _ => None,
+            })
+    }
+
+    fn sort(&mut self, order: SortOrder) {
+        order.sort(&mut self.files, |(path, file)| sort_entry(path, file));
     }
 }
 
 pub struct FlattenEntries<'a, 'e, T> {
     level: Level,
     inner: &'e Entries<'a>,
+    view: View,
     marker: PhantomData<T>,
 }
 
@@ -226,6 +354,8 @@ impl<'a, 'e, T> FlattenEntries<'a, 'e, T> {
         let inner = FlattenFilterEntries {
             inner: self.inner,
             filter,
+            matcher: self.view.filter.matcher(),
+            view: self.view,
             marker: PhantomData,
         };
         inner.into_stream(self.level).await
@@ -235,6 +365,8 @@ impl<'a, 'e, T> FlattenEntries<'a, 'e, T> {
 struct FlattenFilterEntries<'a, 'e, T, Filt> {
     inner: &'e Entries<'a>,
     filter: Filt,
+    matcher: Matcher,
+    view: View,
     marker: PhantomData<T>,
 }
 
@@ -244,14 +376,17 @@ where
     Item: Into<T>,
 {
     async fn iter(self, level: Level) -> FlattenEntriesIter<Filt> {
-        let mut children = self.inner.children().await;
-        children.sort();
-        let stack = vec![(level.increment(), children.into_iter())];
+        let children = self.inner.children().await;
 
-        FlattenEntriesIter {
-            stack,
+        let mut iter = FlattenEntriesIter {
+            stack: Vec::new(),
+            pending: None,
             filter: self.filter,
-        }
+            matcher: self.matcher,
+            view: self.view,
+        };
+        iter.push(level.increment(), self.inner.dir, children);
+        iter
     }
 
     async fn into_stream(self, level: Level) -> impl Stream<Item = T> {
@@ -263,36 +398,105 @@ where
     }
 }
 
+// The children of a directory being flattened.
+struct Frame {
+    level: Level,
+    dir: PathBuf,
+    children: <Children as IntoIterator>::IntoIter,
+    shown: usize,
+    limit: Option<usize>,
+}
+
 struct FlattenEntriesIter<Filt> {
-    stack: Vec<(Level, <Children as IntoIterator>::IntoIter)>,
+    stack: Vec<Frame>,
+    pending: Option<Item>,
     filter: Filt,
+    matcher: Matcher,
+    view: View,
 }
 
 impl<Filt> FlattenEntriesIter<Filt>
 where
     Filt: for<'p> Fn(&'p Path) -> bool,
 {
+    fn push(&mut self, level: Level, dir: &Path, mut children: Children) {
+        self.pending = placeholder(level, dir, children.status);
+        children.sort(self.view.sort);
+
+        let limit = if self.view.uncapped.contains(dir) {
+            None
+        } else {
+            self.view.limit
+        };
+
+        self.stack.push(Frame {
+            level,
+            dir: dir.to_path_buf(),
+            children: children.into_iter(),
+            shown: 0,
+            limit,
+        });
+    }
+
     async fn next_item(&mut self) -> Option<Item> {
-        while let Some(&mut (level, ref mut children)) = self.stack.last_mut() {
-            let Some((child_path, child)) = children.next() else {
+        if let Some(item) = self.pending.take() {
+            return Some(item);
+        }
+
+        while let Some(frame) = self.stack.last_mut() {
+            let Some((child_path, child)) = frame.children.next() else {
                 self.stack.pop();
                 continue;
             };
 
+            let is_dir = matches!(child.follow_link(), File::Directory { .. });
+            if !self.matcher.is_visible(&child_path, is_dir) {
+                continue;
+            }
+
+            if frame.limit.is_some_and(|limit| frame.shown >= limit) {
+                let mut rest = 1;
+                for (path, file) in frame.children.by_ref() {
+                    let is_dir = matches!(file.follow_link(), File::Directory { .. });
+                    if self.matcher.is_visible(&path, is_dir) {
+                        rest += 1;
+                    }
+                }
+
+                let item = Item {
+                    level: frame.level,
+                    path: frame.dir.clone(),
+                    metadata: Metadata {
+                        perm: Permissions::default(),
+                        stat: None,
+                        file_type: FileType::More(rest),
+                    },
+                };
+                self.stack.pop();
+                return Some(item);
+            }
+            frame.shown += 1;
+            let level = frame.level;
+
             let metadata = match child {
-                File::Regular { perm } => Metadata {
+                File::Regular { perm, stat } => Metadata {
                     perm,
+                    stat: Some(stat),
                     file_type: FileType::Regular,
                 },
-                File::Directory { entries, perm } => {
+                File::Directory {
+                    entries,
+                    perm,
+                    stat,
+                } => {
                     if (self.filter)(&child_path) && level < Level::MAX {
-                        let mut children = Entries::children_in(&entries, &child_path).await;
-                        children.sort();
-                        self.stack.push((level.increment(), children.into_iter()));
+                        let children = Entries::children_in(&entries, &child_path).await;
+                        self.push(level.increment(), &child_path, children);
                     }
 
                     Metadata {
                         perm,
+                        stat: Some(stat),
                         file_type: FileType::Directory,
                     }
                 }
@@ -300,30 +504,37 @@ where
                     let file = to.follow_link();
 
                     match file {
-                        &File::Regular { perm } => Metadata {
+                        &File::Regular { perm, stat } => Metadata {
                             perm,
+                            stat: Some(stat),
                             file_type: FileType::LinkRegular,
                         },
-                        File::Directory { entries, perm } => {
+                        File::Directory {
+                            entries,
+                            perm,
+                            stat,
+                        } => {
                             if (self.filter)(&child_path) && level < Level::MAX {
-                                let mut children = Entries::children_in(entries, &child_path).await;
-                                children.sort();
-                                self.stack.push((level.increment(), children.into_iter()));
+                                let children = Entries::children_in(entries, &child_path).await;
+                                self.push(level.increment(), &child_path, children);
                             }
 
                             Metadata {
                                 perm: *perm,
+                                stat: Some(*stat),
                                 file_type: FileType::LinkDirectory,
                             }
                         }
                         _ => Metadata {
                             perm: Permissions::default(),
+                            stat: None,
                             file_type: FileType::LinkOther,
                         },
                     }
                 }
                 _ => Metadata {
                     perm: Permissions::default(),
+                    stat: None,
                     file_type: FileType::Other,
                 },
             };
@@ -339,6 +550,25 @@ where
     }
 }
 
+// Stands in for the entries of a directory that has not been read (yet).
+fn placeholder(level: Level, dir: &Path, status: ReadStatus) -> Option<Item> {
+    let file_type = match status {
+        ReadStatus::Unread => FileType::Loading,
+        ReadStatus::TimedOut => FileType::TimedOut,
+        ReadStatus::Read => return None,
+    };
+
+    Some(Item {
+        level,
+        path: dir.to_path_buf(),
+        metadata: Metadata {
+            perm: Permissions::default(),
+            stat: None,
+            file_type,
+        },
+    })
+}
+
 pub fn find_in_dir(prefix: &Path, lines: &[Item]) -> Range<usize> {
     let mut start = lines.len();
     let mut end = start;
@@ -364,35 +594,168 @@ pub fn find_in_dir(prefix: &Path, lines: &[Item]) -> Range<usize> {
     start..end
 }
 
+pub fn copy_all(src: &Path, dst: &Path) -> Result<(), IoErr> {
+    let meta = src.symlink_metadata()?;
+    let file_type = meta.file_type();
+
+    if file_type.is_symlink() {
+        let target = std::fs::read_link(src)?;
+        std::os::unix::fs::symlink(target, dst)
+    } else if file_type.is_dir() {
+        std::fs::create_dir(dst)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        std::fs::set_permissions(dst, meta.permissions())
+    } else if file_type.is_file() {
+        std::fs::copy(src, dst).map(|_| ())
+    } else {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
+// A name for `fname` that is free in `dest_dir`: "foo.txt" becomes "foo (copy).txt", then
+// "foo (copy 2).txt" and so on, for the label "copy". Directories keep their name whole.
+pub fn free_name(dest_dir: &Path, fname: &OsStr, label: &str, is_dir: bool) -> OsString {
+    let path = Path::new(fname);
+    let (stem, ext) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) if !is_dir => (stem, Some(ext)),
+        _ => (fname, None),
+    };
+
+    (1..)
+        .map(|n| {
+            let mut name = stem.to_os_string();
+            if n == 1 {
+                name.push(format!(" ({label})"));
+            } else {
+                name.push(format!(" ({label} {n})"));
+            }
+            if let Some(ext) = ext {
+                name.push(".");
+                name.push(ext);
+            }
+            name
+        })
+        .find(|name| dest_dir.join(name).symlink_metadata().is_err())
+        .unwrap_or_default()
+}
+
+pub async fn find_level(lines: &Items, path: &Path) -> Option<Level> {
+    lines
+        .iter()
+        .fold(None, |acc, item| level_of(acc, item, path))
+        .await
+}
+
+// Placeholders carry the path of their directory but sit at the level of its children, so
+// they do not count.
+fn level_of(acc: Option<Level>, item: &Item, path: &Path) -> Option<Level> {
+    if item.path == path && item.metadata.placeholder().is_none() {
+        Some(item.level)
+    } else {
+        acc
+    }
+}
+
+async fn insert_sorted(
+    nvim: &Neovim<NvimWtr>,
+    lines: &Items,
+    path: &Path,
+    level: Level,
+    file: &File,
+    view: &View,
+) -> Result<(), NvimErr> {
+    let this = sort_entry(path, file);
+    if !view.filter.matcher().is_visible(path, this.is_dir) {
+        return Ok(());
+    }
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+
+    lines
+        .edit(nvim)
+        .insert_dyn(file_to_item(level, path, file), |lines| {
+            let siblings = find_in_dir(parent, lines);
+
+            // The entry goes before the first sibling that sorts after it, or after the
+            // subtree of the last sibling.
+            let mut idx = siblings.end;
+            for (i, item) in lines[siblings.clone()].iter().enumerate() {
+                if item.level != level {
+                    continue;
+                }
+                let sibling = sort::Entry {
+                    path: &item.path,
+                    is_dir: item.metadata.is_dir(),
+                    stat: item.metadata.stat.as_ref(),
+                };
+                if view.sort.is_before(this, sibling) {
+                    idx = siblings.start + i;
+                    break;
+                }
+            }
+            idx
+        })
+        .await?;
+
+    Ok(())
+}
+
+fn sort_entry<'a>(path: &'a Path, file: &'a File) -> sort::Entry<'a> {
+    let (is_dir, stat) = match file.follow_link() {
+        File::Directory { stat, .. } => (true, Some(stat)),
+        File::Regular { stat, .. } => (false, Some(stat)),
+        _ => (false, None),
+    };
+    sort::Entry { path, is_dir, stat }
+}
+
 pub fn file_to_item(level: Level, path: &Path, file: &File) -> Item {
     let metadata = match file {
-        &File::Regular { perm } => Metadata {
+        &File::Regular { perm, stat } => Metadata {
             perm,
+            stat: Some(stat),
             file_type: FileType::Regular,
         },
-        &File::Directory { perm, entries: _ } => Metadata {
+        &File::Directory {
+            perm,
+            stat,
+            entries: _,
+        } => Metadata {
             perm,
+            stat: Some(stat),
             file_type: FileType::Directory,
         },
         File::Link { to } => {
             let file = to.follow_link();
             match *file {
-                File::Regular { perm } => Metadata {
+                File::Regular { perm, stat } => Metadata {
                     perm,
+                    stat: Some(stat),
                     file_type: FileType::LinkRegular,
                 },
-                File::Directory { perm, entries: _ } => Metadata {
+                File::Directory {
                     perm,
+                    stat,
+                    entries: _,
+                } => Metadata {
+                    perm,
+                    stat: Some(stat),
                     file_type: FileType::LinkDirectory,
                 },
                 _ => Metadata {
                     perm: Permissions::default(),
+                    stat: None,
                     file_type: FileType::LinkOther,
                 },
             }
         }
         _ => Metadata {
             perm: Permissions::default(),
+            stat: None,
             file_type: FileType::Other,
         },
     };
@@ -403,3 +766,109 @@ pub fn file_to_item(level: Level, path: &Path, file: &File) -> Item {
         metadata,
     }
 }
+
+pub fn bookmarks_file() -> Result<PathBuf, Error> {
+    bookmarks::path().ok_or_else(|| {
+        Error::InvalidInput(
+            "cannot store bookmarks: neither $XDG_STATE_HOME nor $HOME is set".into(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::executor::block_on;
+    use futures::stream::StreamExt as _;
+
+    // Nothing is read from here: the entries are made up in the cache.
+    const ROOT: &str = "/nonexistent/lazy-filer-find-level";
+
+    fn level_in(lines: &[Item], path: &Path) -> Option<Level> {
+        lines
+            .iter()
+            .fold(None, |acc, item| level_of(acc, item, path))
+    }
+
+    async fn flatten(entries: &Entries<'_>, level: Level, view: &View) -> Vec<Item> {
+        let expanded = |path: &Path| path.ends_with("sub");
+        entries
+            .flatten(level, view)
+            .filter(expanded)
+            .await
+            .collect()
+            .await
+    }
+
+    // `sub` is expanded, capped at two of its three entries, and not read yet, so it renders
+    // both a "loading" and a "more" placeholder with its own path.
+    #[test]
+    fn capped_directory_keeps_its_level() {
+        block_on(async {
+            let root = Path::new(ROOT);
+            let sub = root.join("sub");
+
+            let sub_entries = fs::Entries::default();
+            for fname in ["a", "b", "c"] {
+                let fname = fs::Component::from_slice(OsStr::new(fname));
+                sub_entries.insert(fname, File::regular(0o644)).await;
+            }
+            let root_entries = fs::Entries::default();
+            let sub_dir = File::Directory {
+                entries: sub_entries.clone(),
+                perm: Permissions::default(),
+                stat: Stat::default(),
+            };
+            let fname = fs::Component::from_slice(OsStr::new("sub"));
+            root_entries.insert(fname, sub_dir).await;
+
+            let view = View {
+                limit: Some(2),
+                ..Default::default()
+            };
+
+            let root_dir = Entries {
+                entries: root_entries,
+                dir: root,
+            };
+            let root_item = Item {
+                level: Level::base(),
+                path: root.to_path_buf(),
+                metadata: Metadata {
+                    perm: Permissions::default(),
+                    stat: None,
+                    file_type: FileType::Directory,
+                },
+            };
+            let mut lines = vec![root_item];
+            lines.extend(flatten(&root_dir, Level::base(), &view).await);
+
+            let sub_level = Level::base().increment();
+            let placeholders: Vec<_> = lines
+                .iter()
+                .filter(|item| item.metadata.placeholder().is_some())
+                .map(|item| (&*item.path, item.level))
+                .collect();
+            assert!(placeholders.contains(&(root, sub_level)));
+            assert!(placeholders.contains(&(&*sub, sub_level.increment())));
+
+            assert_eq!(level_in(&lines, root), Some(Level::base()));
+            assert_eq!(level_in(&lines, &sub), Some(sub_level));
+
+            // Rendering the children of `sub` again, e.g. once its rest is expanded.
+            let sub_dir = Entries {
+                entries: sub_entries,
+                dir: &sub,
+            };
+            let level = level_in(&lines, &sub).unwrap();
+            let children = flatten(&sub_dir, level, &view).await;
+            assert!(!children.is_empty());
+            assert!(
+                children
+                    .iter()
+                    .all(|item| item.level == sub_level.increment())
+            );
+        });
+    }
+}
@@ -0,0 +1,296 @@
+use super::States;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use inotify::Watcher;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub use fallback::Watcher;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod inotify {
+    use super::super::NvimWtr;
+    use nvim_router::nvim_rs::Neovim;
+
+    use super::super::refresh::refresh_dirs;
+    use super::States;
+
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::ffi::{OsStr, OsString};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tokio::sync::Mutex;
+    use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+    use nix::errno::Errno;
+    use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
+
+    const DEBOUNCE: Duration = Duration::from_millis(100);
+    // Events that keep coming, e.g. from a build writing into the tree, are flushed at least
+    // this often.
+    const MAX_DELAY: Duration = Duration::from_millis(1000);
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Watcher(Arc<Mutex<Option<Inner>>>);
+
+    #[derive(Debug)]
+    struct Inner {
+        inotify: Arc<Inotify>,
+        stopped: Arc<AtomicBool>,
+        watches: BTreeMap<PathBuf, WatchDescriptor>,
+        dirs: BTreeMap<WatchDescriptor, PathBuf>,
+    }
+
+    enum Event {
+        Changed(WatchDescriptor, Option<OsString>),
+        Ignored(WatchDescriptor),
+    }
+
+    fn watch_flags() -> AddWatchFlags {
+        AddWatchFlags::IN_CREATE
+            | AddWatchFlags::IN_DELETE
+            | AddWatchFlags::IN_MOVED_FROM
+            | AddWatchFlags::IN_MOVED_TO
+            | AddWatchFlags::IN_ATTRIB
+            | AddWatchFlags::IN_ONLYDIR
+    }
+
+    impl Inner {
+        fn add(&mut self, path: &Path) {
+            if self.watches.contains_key(path) {
+                return;
+            }
+
+            if let Ok(wd) = self.inotify.add_watch(path, watch_flags()) {
+                self.watches.insert(path.to_path_buf(), wd);
+                self.dirs.insert(wd, path.to_path_buf());
+            }
+        }
+
+        fn remove(&mut self, path: &Path) {
+            let Some(wd) = self.watches.remove(path) else {
+                return;
+            };
+
+            // The kernel hands out a single descriptor per inode, so a directory reachable
+            // through a symlink may still be watched under another path.
+            if let Some((other, _)) = self.watches.iter().find(|(_, other)| **other == wd) {
+                self.dirs.insert(wd, other.to_path_buf());
+            } else {
+                self.dirs.remove(&wd);
+                self.inotify.rm_watch(wd).ok();
+            }
+        }
+
+        fn forget(&mut self, wd: WatchDescriptor) {
+            self.dirs.remove(&wd);
+            self.watches.retain(|_, other| *other != wd);
+        }
+    }
+
+    impl Watcher {
+        pub async fn start(
+            &self,
+            nvim: &Neovim<NvimWtr>,
+            states: &States,
+            dirs: &BTreeSet<PathBuf>,
+        ) {
+            let mut lock = self.0.lock().await;
+            if lock.is_some() {
+                return;
+            }
+
+            let Ok(inotify) = Inotify::init(InitFlags::IN_CLOEXEC) else {
+                return;
+            };
+            let mut inner = Inner {
+                inotify: Arc::new(inotify),
+                stopped: Default::default(),
+                watches: Default::default(),
+                dirs: Default::default(),
+            };
+            for dir in dirs {
+                inner.add(dir);
+            }
+
+            let (tx, rx) = mpsc::unbounded_channel();
+
+            let inotify = inner.inotify.clone();
+            let stopped = inner.stopped.clone();
+            std::thread::spawn(move || read_events(&inotify, &stopped, &tx));
+
+            tokio::spawn(handle_events(nvim.clone(), states.clone(), rx));
+
+            *lock = Some(inner);
+        }
+
+        // The reader thread blocks in read(2). Removing the watches queues IN_IGNORED for each
+        // of them, which wakes it up to see the flag and drop the channel.
+        pub async fn stop(&self) {
+            let Some(inner) = self.0.lock().await.take() else {
+                return;
+            };
+
+            inner.stopped.store(true, Ordering::Release);
+            for wd in inner.dirs.keys() {
+                inner.inotify.rm_watch(*wd).ok();
+            }
+        }
+
+        pub async fn add(&self, path: &Path) {
+            if let Some(inner) = self.0.lock().await.as_mut() {
+                inner.add(path);
+            }
+        }
+
+        pub async fn remove(&self, path: &Path) {
+            if let Some(inner) = self.0.lock().await.as_mut() {
+                inner.remove(path);
+            }
+        }
+
+        pub async fn update(&self, old: &BTreeSet<PathBuf>, new: &BTreeSet<PathBuf>) {
+            if let Some(inner) = self.0.lock().await.as_mut() {
+                for path in old.difference(new) {
+                    inner.remove(path);
+                }
+                for path in new.difference(old) {
+                    inner.add(path);
+                }
+            }
+        }
+
+        async fn forget(&self, wd: WatchDescriptor) {
+            if let Some(inner) = self.0.lock().await.as_mut() {
+                inner.forget(wd);
+            }
+        }
+
+        async fn dirs(&self, changes: &[(WatchDescriptor, Option<OsString>)]) -> BTreeSet<PathBuf> {
+            let lock = self.0.lock().await;
+            let Some(inner) = lock.as_ref() else {
+                return Default::default();
+            };
+
+            changes
+                .iter()
+                .filter_map(|(wd, name)| {
+                    let dir = inner.dirs.get(wd)?;
+                    if is_git_internal(dir, name.as_deref()) {
+                        None
+                    } else {
+                        Some(dir.clone())
+                    }
+                })
+                .collect()
+        }
+    }
+
+    // Git writes under its directory all the time, index.lock above all. Only the index, HEAD
+    // and refs change the status, and refreshing on the rest could loop with `git status`.
+    fn is_git_internal(dir: &Path, name: Option<&OsStr>) -> bool {
+        let mut components = dir.components();
+        if !components.any(|c| c.as_os_str() == ".git") {
+            return false;
+        }
+
+        match components.next() {
+            Some(c) => c.as_os_str() != "refs",
+            None => !matches!(
+                name.and_then(OsStr::to_str),
+                Some("index" | "HEAD" | "refs")
+            ),
+        }
+    }
+
+    fn read_events(inotify: &Inotify, stopped: &AtomicBool, tx: &UnboundedSender<Event>) {
+        loop {
+            let events = match inotify.read_events() {
+                Ok(events) => events,
+                Err(Errno::EINTR) => continue,
+                Err(_) => return,
+            };
+
+            if stopped.load(Ordering::Acquire) {
+                return;
+            }
+
+            for event in events {
+                let event = if event.mask.contains(AddWatchFlags::IN_IGNORED) {
+                    Event::Ignored(event.wd)
+                } else {
+                    Event::Changed(event.wd, event.name)
+                };
+
+                if tx.send(event).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn handle_events(
+        nvim: Neovim<NvimWtr>,
+        states: States,
+        mut rx: UnboundedReceiver<Event>,
+    ) {
+        let watcher = states.actions.expanded_dir.watcher();
+
+        while let Some(event) = rx.recv().await {
+            let mut changed = Vec::new();
+            let mut next = Some(event);
+            let deadline = Instant::now() + MAX_DELAY;
+
+            while let Some(event) = next {
+                match event {
+                    Event::Changed(wd, name) => {
+                        changed.push((wd, name));
+                    }
+                    Event::Ignored(wd) => {
+                        changed.retain(|(changed, _)| *changed != wd);
+                        watcher.forget(wd).await;
+                    }
+                }
+
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                let wait = DEBOUNCE.min(deadline - now);
+                next = tokio::time::timeout(wait, rx.recv()).await.ok().flatten();
+            }
+
+            let dirs = watcher.dirs(&changed).await;
+            refresh_dirs(&nvim, &states, &dirs).await.ok();
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod fallback {
+    use super::super::NvimWtr;
+    use nvim_router::nvim_rs::Neovim;
+
+    use super::States;
+
+    use std::collections::BTreeSet;
+    use std::path::{Path, PathBuf};
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Watcher;
+
+    impl Watcher {
+        pub async fn start(&self, _: &Neovim<NvimWtr>, _: &States, _: &BTreeSet<PathBuf>) {}
+
+        pub async fn stop(&self) {}
+
+        pub async fn add(&self, _: &Path) {}
+
+        pub async fn remove(&self, _: &Path) {}
+
+        pub async fn update(&self, _: &BTreeSet<PathBuf>, _: &BTreeSet<PathBuf>) {}
+    }
+}
@@ -0,0 +1,47 @@
+use super::NvimWtr;
+use nvim_router::nvim_rs::Neovim;
+
+use super::renderer::LineIdx;
+use super::states::YankMode;
+use super::{Action, Error, States};
+
+pub struct YankEntry {
+    pub line_idx: LineIdx,
+    pub mode: YankMode,
+    pub nvim: Neovim<NvimWtr>,
+}
+
+impl Action for YankEntry {
+    type Resp = ();
+
+    async fn run(&self, states: &States) -> Result<Self::Resp, Error> {
+        let lines = &states.actions.rendered_lines;
+
+        let selection = lines.selection().take().await;
+        if !selection.is_empty() {
+            let mut paths: Vec<_> = Vec::new();
+            for path in selection {
+                if !paths.iter().any(|yanked| path.starts_with(yanked)) {
+                    paths.push(path);
+                }
+            }
+            states.actions.register.set(self.mode, paths).await;
+
+            lines.edit(&self.nvim).redraw(|_| ..).await?;
+
+            return Ok(());
+        }
+
+        let Some(path) = lines
+            .get(self.line_idx)
+            .and_then(|item| Some(item.path.to_path_buf()))
+            .await
+        else {
+            return Ok(());
+        };
+
+        states.actions.register.set(self.mode, vec![path]).await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::session;
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io::{Error as IoError, ErrorKind};
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+
+// Named directories, shared by every Neovim process. The file is read again on every change,
+// so that bookmarks added elsewhere are not lost.
+pub type Bookmarks = BTreeMap<String, PathBuf>;
+
+// A tab separates a name from its path, one bookmark per line.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['\t', '\n'])
+}
+
+pub fn path() -> Option<PathBuf> {
+    Some(session::state_dir()?.join("bookmarks"))
+}
+
+pub fn load(file: &Path) -> Result<Bookmarks, IoError> {
+    let content = match std::fs::read(file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Bookmarks::new()),
+        Err(e) => return Err(e),
+    };
+
+    let bookmarks = content
+        .split(|&b| b == b'\n')
+        .filter_map(|line| {
+            let tab = line.iter().position(|&b| b == b'\t')?;
+            let name = std::str::from_utf8(&line[..tab]).ok()?;
+            let path = Path::new(OsStr::from_bytes(&line[tab + 1..]));
+            path.is_absolute()
+                .then(|| (name.to_string(), path.to_path_buf()))
+        })
+        .collect();
+
+    Ok(bookmarks)
+}
+
+pub fn update<F>(file: &Path, f: F) -> Result<(), IoError>
+where
+    F: FnOnce(&mut Bookmarks),
+{
+    let mut bookmarks = load(file)?;
+    f(&mut bookmarks);
+
+    let mut content = Vec::new();
+    for (name, path) in &bookmarks {
+        let bytes = path.as_os_str().as_bytes();
+        if bytes.contains(&b'\n') {
+            continue;
+        }
+        content.extend_from_slice(name.as_bytes());
+        content.push(b'\t');
+        content.extend_from_slice(bytes);
+        content.push(b'\n');
+    }
+
+    session::write_atomic(file, &content)
+}
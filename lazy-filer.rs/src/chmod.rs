@@ -0,0 +1,220 @@
+use std::fs::Permissions;
+use std::io::Error as IoError;
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Path;
+
+// A mode as accepted by chmod(1): octal (`755`) or symbolic (`u+x,go-w`, `a=rX`, `g=u`).
+// Unlike chmod(1), a symbolic clause without `ugoa` applies to all of them regardless of the umask.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Octal(u32),
+    Symbolic(Vec<Clause>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause {
+    who: u32,
+    actions: Vec<(Op, Perm)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Remove,
+    Set,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Perm {
+    Letters { bits: u32, cond_exec: bool },
+    Copy(u32),
+}
+
+// The bits each of `ugo` may change.
+const USER: u32 = 0o4700;
+const GROUP: u32 = 0o2070;
+const OTHER: u32 = 0o1007;
+
+impl Mode {
+    pub fn parse(s: &str) -> Option<Self> {
+        if !s.is_empty() && s.len() <= 4 && s.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+            return u32::from_str_radix(s, 8).ok().map(Self::Octal);
+        }
+
+        s.split(',')
+            .map(Clause::parse)
+            .collect::<Option<Vec<_>>>()
+            .map(Self::Symbolic)
+    }
+
+    pub fn apply(&self, mode: u32, is_dir: bool) -> u32 {
+        match self {
+            Self::Octal(new) => new & 0o7777,
+            Self::Symbolic(clauses) => clauses
+                .iter()
+                .fold(mode & 0o7777, |mode, clause| clause.apply(mode, is_dir)),
+        }
+    }
+}
+
+impl Clause {
+    fn parse(s: &str) -> Option<Self> {
+        let mut chars = s.chars().peekable();
+
+        let mut who = 0;
+        while let Some(&c) = chars.peek() {
+            who |= match c {
+                'u' => USER,
+                'g' => GROUP,
+                'o' => OTHER,
+                'a' => USER | GROUP | OTHER,
+                _ => break,
+            };
+            chars.next();
+        }
+        if who == 0 {
+            who = USER | GROUP | OTHER;
+        }
+
+        let mut actions = Vec::new();
+        while let Some(c) = chars.next() {
+            let op = match c {
+                '+' => Op::Add,
+                '-' => Op::Remove,
+                '=' => Op::Set,
+                _ => return None,
+            };
+
+            let perm = match chars.peek() {
+                Some(&c @ ('u' | 'g' | 'o')) => {
+                    chars.next();
+                    let shift = match c {
+                        'u' => 6,
+                        'g' => 3,
+                        _ => 0,
+                    };
+                    Perm::Copy(shift)
+                }
+                _ => {
+                    let mut bits = 0;
+                    let mut cond_exec = false;
+                    while let Some(&c) = chars.peek() {
+                        match c {
+                            'r' => bits |= 0o444,
+                            'w' => bits |= 0o222,
+                            'x' => bits |= 0o111,
+                            'X' => cond_exec = true,
+                            's' => bits |= 0o6000,
+                            't' => bits |= 0o1000,
+                            _ => break,
+                        }
+                        chars.next();
+                    }
+                    Perm::Letters { bits, cond_exec }
+                }
+            };
+
+            actions.push((op, perm));
+        }
+
+        if actions.is_empty() {
+            return None;
+        }
+        Some(Self { who, actions })
+    }
+
+    fn apply(&self, mut mode: u32, is_dir: bool) -> u32 {
+        for &(op, perm) in &self.actions {
+            let bits = match perm {
+                Perm::Letters { bits, cond_exec } => {
+                    // X: executable only for directories, or files already executable by someone.
+                    if cond_exec && (is_dir || mode & 0o111 != 0) {
+                        bits | 0o111
+                    } else {
+                        bits
+                    }
+                }
+                Perm::Copy(shift) => ((mode >> shift) & 0o7) * 0o111,
+            } & self.who;
+
+            mode = match op {
+                Op::Add => mode | bits,
+                Op::Remove => mode & !bits,
+                Op::Set => (mode & !self.who) | bits,
+            };
+        }
+
+        mode
+    }
+}
+
+// Symlinks are followed at `path` itself but not inside it, as chmod -R does.
+pub fn chmod(path: &Path, mode: &Mode, recursive: bool) -> Result<(), IoError> {
+    let meta = path.metadata()?;
+    let new = mode.apply(meta.permissions().mode(), meta.is_dir());
+    std::fs::set_permissions(path, Permissions::from_mode(new))?;
+
+    if !(recursive && meta.is_dir()) {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_symlink() {
+            continue;
+        }
+        chmod(&entry.path(), mode, true)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(mode: &str, old: u32, is_dir: bool) -> u32 {
+        Mode::parse(mode).unwrap().apply(old, is_dir)
+    }
+
+    #[test]
+    fn several_clauses() {
+        assert_eq!(apply("u+x,go-w", 0o666, false), 0o744);
+    }
+
+    #[test]
+    fn conditional_exec() {
+        assert_eq!(apply("a=rX", 0o640, false), 0o444);
+        assert_eq!(apply("a=rX", 0o740, false), 0o555);
+        assert_eq!(apply("a=rX", 0o700, true), 0o555);
+    }
+
+    #[test]
+    fn copy_from_user() {
+        assert_eq!(apply("g=u", 0o640, false), 0o660);
+        assert_eq!(apply("g=u", 0o4750, false), 0o4770);
+    }
+
+    #[test]
+    fn sticky_bit() {
+        assert_eq!(apply("+t", 0o777, true), 0o1777);
+    }
+
+    #[test]
+    fn setid_for_others_is_ignored() {
+        assert_eq!(apply("o+s", 0o755, false), 0o755);
+    }
+
+    #[test]
+    fn octal() {
+        assert_eq!(Mode::parse("0755"), Some(Mode::Octal(0o755)));
+        assert_eq!(apply("0755", 0o100644, false), 0o755);
+    }
+
+    #[test]
+    fn rejected() {
+        assert_eq!(Mode::parse("u"), None);
+        assert_eq!(Mode::parse("8"), None);
+        assert_eq!(Mode::parse("u+q"), None);
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Local, TimeZone as _};
+use nix::unistd::{Gid, Group, Uid, User};
+
+use std::collections::{BTreeSet, HashMap};
+
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+// 1023, 1.0K, 9.9K, 10K, 1023K, 1.0M, ...
+pub fn human_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1023.5 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if value < 9.95 {
+        format!("{value:.1}{}", UNITS[unit])
+    } else {
+        format!("{value:.0}{}", UNITS[unit])
+    }
+}
+
+// -rwxr-xr-x, drwxrwxrwt, ...
+pub fn mode_string(mode: u32) -> String {
+    let file_type = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    };
+
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    let special = |exec: u32, special: u32, set: char| match (mode & exec != 0, mode & special != 0)
+    {
+        (true, true) => set,
+        (false, true) => set.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [
+        file_type,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        special(0o100, 0o4000, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        special(0o010, 0o2000, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        special(0o001, 0o1000, 't'),
+    ]
+    .into_iter()
+    .collect()
+}
+
+pub fn is_valid_time_format(fmt: &str) -> bool {
+    !StrftimeItems::new(fmt).any(|item| matches!(item, Item::Error))
+}
+
+// `fmt` must have been checked with is_valid_time_format(), as an invalid one panics.
+pub fn format_time(secs: i64, fmt: &str) -> String {
+    match Local.timestamp_opt(secs, 0).single() {
+        Some(time) => time.format(fmt).to_string(),
+        None => secs.to_string(),
+    }
+}
+
+// Owner and group names, looked up once per id. Ids without a name are shown as numbers.
+#[derive(Debug, Default)]
+pub struct Names {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl Names {
+    // Ids of the (uid, gid) pairs that have not been looked up yet.
+    pub fn missing(&self, ids: impl IntoIterator<Item = (u32, u32)>) -> Ids {
+        let mut missing = Ids::default();
+        for (uid, gid) in ids {
+            if !self.users.contains_key(&uid) {
+                missing.users.insert(uid);
+            }
+            if !self.groups.contains_key(&gid) {
+                missing.groups.insert(gid);
+            }
+        }
+        missing
+    }
+
+    pub fn extend(&mut self, names: Names) {
+        self.users.extend(names.users);
+        self.groups.extend(names.groups);
+    }
+
+    pub fn user(&self, uid: u32) -> String {
+        self.users
+            .get(&uid)
+            .cloned()
+            .unwrap_or_else(|| uid.to_string())
+    }
+
+    pub fn group(&self, gid: u32) -> String {
+        self.groups
+            .get(&gid)
+            .cloned()
+            .unwrap_or_else(|| gid.to_string())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Ids {
+    users: BTreeSet<u32>,
+    groups: BTreeSet<u32>,
+}
+
+impl Ids {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.groups.is_empty()
+    }
+
+    // May block on NSS, which can ask a directory service over the network.
+    pub fn lookup(self) -> Names {
+        let users = self.users.into_iter().map(|uid| {
+            let name = User::from_uid(Uid::from_raw(uid))
+                .ok()
+                .flatten()
+                .map_or_else(|| uid.to_string(), |user| user.name);
+            (uid, name)
+        });
+        let groups = self.groups.into_iter().map(|gid| {
+            let name = Group::from_gid(Gid::from_raw(gid))
+                .ok()
+                .flatten()
+                .map_or_else(|| gid.to_string(), |group| group.name);
+            (gid, name)
+        });
+
+        Names {
+            users: users.collect(),
+            groups: groups.collect(),
+        }
+    }
+}
@@ -5,8 +5,11 @@ use std::io::Error as IoError;
 use std::path::{Path, PathBuf};
 
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{Mutex, MutexGuard};
 
+const READDIR_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Component(OsString);
 
@@ -27,6 +30,53 @@ impl AsRef<Path> for Component {
     }
 }
 
+// What ls -l shows, from stat(2). Times are in seconds since the epoch.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stat {
+    pub size: u64,
+    pub mtime: i64,
+    pub mtime_nsec: i64,
+    pub ctime: i64,
+    pub btime: Option<i64>,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    pub nlink: u64,
+    pub ino: u64,
+}
+
+impl Stat {
+    pub fn read_from_path(path: &Path) -> Self {
+        path.metadata()
+            .map(|meta| Self::from_std(&meta))
+            .unwrap_or_default()
+    }
+
+    fn from_std(meta: &std::fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        use std::time::UNIX_EPOCH;
+
+        let btime = meta
+            .created()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .and_then(|time| i64::try_from(time.as_secs()).ok());
+
+        Self {
+            size: meta.size(),
+            mtime: meta.mtime(),
+            mtime_nsec: meta.mtime_nsec(),
+            ctime: meta.ctime(),
+            btime,
+            uid: meta.uid(),
+            gid: meta.gid(),
+            mode: meta.mode(),
+            nlink: meta.nlink(),
+            ino: meta.ino(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Permissions {
     pub read: bool,
@@ -90,13 +140,31 @@ impl Permissions {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadStatus {
+    #[default]
+    Unread,
+    Read,
+    TimedOut,
+}
+
+#[derive(Debug, Default)]
+struct Dir {
+    files: BTreeMap<Component, File>,
+    status: ReadStatus,
+}
+
 #[derive(Debug, Clone, Default)]
-pub struct Entries(Arc<Mutex<BTreeMap<Component, File>>>);
+pub struct Entries(Arc<Mutex<Dir>>);
 
-pub struct ChildrenIntoIter<'a>(MutexGuard<'a, BTreeMap<Component, File>>);
+pub struct ChildrenIntoIter<'a>(MutexGuard<'a, Dir>);
 impl ChildrenIntoIter<'_> {
     pub fn iter(&self) -> impl Iterator<Item = (&Component, File)> + '_ {
-        self.0.iter().map(|(k, v)| (k, v.clone()))
+        self.0.files.iter().map(|(k, v)| (k, v.clone()))
+    }
+
+    pub fn status(&self) -> ReadStatus {
+        self.0.status
     }
 }
 
@@ -106,7 +174,7 @@ impl Entries {
         Component: Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        self.0.lock().await.get(key).cloned()
+        self.0.lock().await.files.get(key).cloned()
     }
 
     pub async fn remove<Q>(&self, key: &Q) -> Option<File>
@@ -114,93 +182,81 @@ impl Entries {
         Component: Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        self.0.lock().await.remove(key)
+        self.0.lock().await.files.remove(key)
     }
 
     pub async fn insert(&self, key: Component, val: File) {
-        self.0.lock().await.insert(key, val);
+        self.0.lock().await.files.insert(key, val);
     }
 
     pub async fn clear(&self) -> BTreeMap<Component, File> {
         let mut lock = self.0.lock().await;
-        std::mem::take(&mut lock)
+        std::mem::take(&mut lock.files)
     }
 
     pub async fn children(&self) -> ChildrenIntoIter<'_> {
         ChildrenIntoIter(self.0.lock().await)
     }
 
+    // readdir(2) and stat(2) may block for long on a slow filesystem, so they run on the
+    // blocking pool. If they do not finish in time, the directory is marked as timed out and
+    // the cached entries are left as they are.
     pub async fn update_with_readdir(&self, dir: &Path) -> Result<(), IoError> {
-        let mut new_entries: Vec<(Component, File)> = Default::default();
-
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-
-            let fname = Component(entry.file_name());
-
-            let file = if file_type.is_file() {
-                let metadata = entry.metadata()?;
-                let perm = Permissions::from_std(metadata);
-                File::Regular { perm }
-            } else if file_type.is_dir() {
-                let metadata = entry.metadata()?;
-                let perm = Permissions::from_std(metadata);
-
-                File::Directory {
-                    entries: Default::default(),
-                    perm,
-                }
-            } else if file_type.is_symlink() {
-                let path = entry.path();
-                let file = if path.is_file() {
-                    let metadata = path.metadata()?;
-                    let perm = Permissions::from_std(metadata);
-                    File::Regular { perm }
-                } else if path.is_dir() {
-                    let metadata = path.metadata()?;
-                    let perm = Permissions::from_std(metadata);
-                    File::Directory {
-                        entries: Default::default(),
-                        perm,
-                    }
-                } else {
-                    File::Other
-                };
-                File::Link { to: Box::new(file) }
-            } else {
-                File::Other
-            };
+        let task = {
+            let dir = dir.to_path_buf();
+            tokio::task::spawn_blocking(move || read_dir(&dir))
+        };
+
+        let new_entries = match tokio::time::timeout(READDIR_TIMEOUT, task).await {
+            Ok(Ok(res)) => res,
+            Ok(Err(e)) => Err(IoError::other(e)),
+            Err(_) => {
+                self.0.lock().await.status = ReadStatus::TimedOut;
+                return Ok(());
+            }
+        };
 
-            new_entries.push((fname, file));
-        }
+        let mut lock = self.0.lock().await;
+        lock.status = ReadStatus::Read;
+        let new_entries = new_entries?;
 
         let new_keys: BTreeSet<_> = new_entries.iter().map(|(k, _)| k).collect();
-        let mut lock = self.0.lock().await;
-        lock.retain(|k, _| new_keys.contains(k));
+        lock.files.retain(|k, _| new_keys.contains(k));
 
         for (key, new_file) in new_entries {
-            if let Some(old_file) = lock.get_mut(&key) {
+            if let Some(old_file) = lock.files.get_mut(&key) {
                 match (old_file, new_file) {
                     (
-                        File::Directory { perm, entries: _ },
+                        File::Directory {
+                            perm,
+                            stat,
+                            entries: _,
+                        },
                         File::Directory {
                             perm: new_perm,
+                            stat: new_stat,
                             entries: _,
                         },
                     ) => {
                         *perm = new_perm;
+                        *stat = new_stat;
                     }
                     (File::Link { to: old_to }, File::Link { to: new_to }) => {
                         match (old_to.follow_link_mut(), new_to.follow_link_owned()) {
                             (
-                                File::Directory { perm, entries: _ },
+                                File::Directory {
+                                    perm,
+                                    stat,
+                                    entries: _,
+                                },
                                 File::Directory {
                                     perm: new_perm,
+                                    stat: new_stat,
                                     entries: _,
                                 },
                             ) => {
                                 *perm = new_perm;
+                                *stat = new_stat;
                             }
                             (old_to, new_to) => *old_to = new_to,
                         }
@@ -210,7 +266,7 @@ impl Entries {
                     }
                 }
             } else {
-                lock.insert(key, new_file);
+                lock.files.insert(key, new_file);
             }
         }
 
@@ -218,15 +274,82 @@ impl Entries {
     }
 }
 
+fn read_dir(dir: &Path) -> Result<Vec<(Component, File)>, IoError> {
+    let mut new_entries = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+
+        let fname = Component(entry.file_name());
+        let file = File::read_with_type(&entry.path(), file_type)?;
+
+        new_entries.push((fname, file));
+    }
+
+    Ok(new_entries)
+}
+
 #[derive(Debug, Clone)]
 pub enum File {
-    Regular { perm: Permissions },
-    Directory { entries: Entries, perm: Permissions },
-    Link { to: Box<File> },
+    Regular {
+        perm: Permissions,
+        stat: Stat,
+    },
+    Directory {
+        entries: Entries,
+        perm: Permissions,
+        stat: Stat,
+    },
+    Link {
+        to: Box<File>,
+    },
     Other,
 }
 
 impl File {
+    pub fn read_from_path(path: &Path) -> Result<Self, IoError> {
+        let file_type = path.symlink_metadata()?.file_type();
+        Self::read_with_type(path, file_type)
+    }
+
+    fn read_with_type(path: &Path, file_type: std::fs::FileType) -> Result<Self, IoError> {
+        let file = if file_type.is_file() || file_type.is_dir() {
+            Self::read_target(path)?
+        } else if file_type.is_symlink() {
+            let file = if path.is_file() || path.is_dir() {
+                Self::read_target(path)?
+            } else {
+                File::Other
+            };
+            File::Link { to: Box::new(file) }
+        } else {
+            File::Other
+        };
+
+        Ok(file)
+    }
+
+    // Reads a regular file or a directory, following symlinks.
+    fn read_target(path: &Path) -> Result<Self, IoError> {
+        let metadata = path.metadata()?;
+        let stat = Stat::from_std(&metadata);
+        let is_dir = metadata.is_dir();
+        let perm = Permissions::from_std(metadata);
+
+        let file = if is_dir {
+            File::Directory {
+                entries: Default::default(),
+                perm,
+                stat,
+            }
+        } else {
+            File::Regular { perm, stat }
+        };
+
+        Ok(file)
+    }
+
     pub fn follow_link(&self) -> &Self {
         let mut ret = self;
         loop {
@@ -260,6 +383,7 @@ impl File {
     pub fn regular(perm: u32) -> Self {
         Self::Regular {
             perm: Permissions::from_raw(perm),
+            stat: Stat::default(),
         }
     }
 
@@ -267,6 +391,7 @@ impl File {
         Self::Directory {
             entries: Default::default(),
             perm: Permissions::from_raw(perm),
+            stat: Stat::default(),
         }
     }
 }
@@ -297,18 +422,18 @@ impl RootFile {
                     File::Directory {
                         entries: next,
                         perm: _,
+                        stat: _,
                     } => entries = next.clone(),
                     _ => unimplemented!(),
                 }
             } else {
-                let perm = stack
-                    .metadata()
-                    .map(Permissions::from_std)
-                    .unwrap_or_default();
+                let perm = Permissions::read_from_path(&stack);
+                let stat = Stat::read_from_path(&stack);
                 let next = Entries::default();
                 let real_dir = File::Directory {
                     entries: next.clone(),
                     perm,
+                    stat,
                 };
 
                 if is_symlink {
@@ -0,0 +1,100 @@
+// A small subsequence matcher in the spirit of fzf's v1 algorithm: the first match found from
+// the left is tightened from the right, then scored by how "word-like" the matched positions
+// are.
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 10;
+const BONUS_CAMEL: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 8;
+const BONUS_FNAME: i64 = 24;
+const PENALTY_GAP: i64 = 1;
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    chars: Vec<char>,
+    case_sensitive: bool,
+}
+
+impl Pattern {
+    // Smart case: the query is case-sensitive only if it has an uppercase letter.
+    pub fn new(query: &str) -> Self {
+        let chars: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+        let case_sensitive = chars.iter().any(|c| c.is_uppercase());
+        Self {
+            chars,
+            case_sensitive,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn score(&self, haystack: &str) -> Option<i64> {
+        let hay: Vec<char> = haystack.chars().collect();
+        let eq = |h: char, p: char| {
+            if self.case_sensitive {
+                h == p
+            } else {
+                h.to_lowercase().eq(p.to_lowercase())
+            }
+        };
+
+        // Leftmost end of a match.
+        let mut pi = 0;
+        let mut end = None;
+        for (i, &h) in hay.iter().enumerate() {
+            if eq(h, self.chars[pi]) {
+                pi += 1;
+                if pi == self.chars.len() {
+                    end = Some(i);
+                    break;
+                }
+            }
+        }
+        let end = end?;
+
+        // Rightmost start for that end, i.e. the shortest window.
+        let mut positions = vec![0; self.chars.len()];
+        let mut pi = self.chars.len();
+        for i in (0..=end).rev() {
+            if eq(hay[i], self.chars[pi - 1]) {
+                pi -= 1;
+                positions[pi] = i;
+                if pi == 0 {
+                    break;
+                }
+            }
+        }
+
+        let fname_start = hay.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+
+        let mut score = 0;
+        let mut prev: Option<usize> = None;
+        for &i in &positions {
+            score += SCORE_MATCH;
+
+            let before = i.checked_sub(1).map(|j| hay[j]);
+            match before {
+                None | Some('/' | '_' | '-' | '.' | ' ') => score += BONUS_BOUNDARY,
+                Some(c) if c.is_lowercase() && hay[i].is_uppercase() => score += BONUS_CAMEL,
+                _ => {}
+            }
+
+            if let Some(prev) = prev {
+                if i == prev + 1 {
+                    score += BONUS_CONSECUTIVE;
+                } else {
+                    score -= PENALTY_GAP * (i - prev - 1) as i64;
+                }
+            }
+            prev = Some(i);
+        }
+
+        if positions[0] >= fname_start {
+            score += BONUS_FNAME;
+        }
+
+        Some(score)
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io::Error as IoError;
+use std::ops::Bound;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Modified,
+    Staged,
+    Untracked,
+    Ignored,
+    Conflicted,
+    // A directory that is clean itself but has changed entries below it.
+    Dirty,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RepoStatus {
+    entries: BTreeMap<PathBuf, Status>,
+}
+
+impl Status {
+    fn from_xy(x: u8, y: u8) -> Self {
+        match (x, y) {
+            (b'?', b'?') => Self::Untracked,
+            (b'!', b'!') => Self::Ignored,
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => Self::Conflicted,
+            (_, b' ') => Self::Staged,
+            _ => Self::Modified,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Modified => "modified",
+            Self::Staged => "staged",
+            Self::Untracked => "untracked",
+            Self::Ignored => "ignored",
+            Self::Conflicted => "conflicted",
+            Self::Dirty => "dirty",
+        }
+    }
+}
+
+impl RepoStatus {
+    // Runs `git status` synchronously; callers are expected to move this off the async runtime.
+    pub fn read(root: &Path) -> Result<Self, IoError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(root)
+            // Otherwise git refreshes the index and takes index.lock, which fails the user's own
+            // commands that run meanwhile.
+            .args([
+                "--no-optional-locks",
+                "status",
+                "--porcelain=v1",
+                "-z",
+                "--ignored=matching",
+                "--untracked-files=normal",
+            ])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(IoError::other("git status failed"));
+        }
+
+        let mut entries = BTreeMap::new();
+        let mut records = output.stdout.split(|&b| b == 0);
+
+        while let Some(record) = records.next() {
+            let [x, y, b' ', path @ ..] = record else {
+                continue;
+            };
+
+            // Renames and copies are followed by the original path, which is no longer there.
+            if matches!(x, b'R' | b'C') {
+                records.next();
+            }
+
+            let path = root.join(OsStr::from_bytes(path));
+            entries.insert(path, Status::from_xy(*x, *y));
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, path: &Path, is_dir: bool) -> Option<Status> {
+        if let Some(&status) = self.entries.get(path) {
+            return Some(status);
+        }
+
+        // git reports an untracked or ignored directory as a whole.
+        for anc in path.ancestors().skip(1) {
+            if let Some(&status @ (Status::Untracked | Status::Ignored)) = self.entries.get(anc) {
+                return Some(status);
+            }
+        }
+
+        if is_dir {
+            let range = (Bound::Excluded(path), Bound::Unbounded);
+            let dirty = self
+                .entries
+                .range::<Path, _>(range)
+                .take_while(|(child, _)| child.starts_with(path))
+                .any(|(_, &status)| status != Status::Ignored);
+            if dirty {
+                return Some(Status::Dirty);
+            }
+        }
+
+        None
+    }
+}
+
+pub fn repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
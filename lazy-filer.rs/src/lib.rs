@@ -1,10 +1,24 @@
 mod states;
-use states::States;
+use states::{Instances, States};
 
+mod bookmarks;
+mod chmod;
+mod columns;
 mod fs;
+mod fuzzy;
+mod git;
+mod preview;
+mod session;
+mod sort;
+mod trash;
+mod visibility;
 
 mod actions;
 use actions::prelude::*;
+use actions::states::{FilterKind, InstanceId, LinkKind, OpenMode, YankMode};
+use preview::Limits;
+use sort::SortOrder;
+use visibility::Filter;
 
 use tokio::fs::File as TokioFile;
 
@@ -17,23 +31,36 @@ type NvimErr = Box<nvim_router::nvim_rs::error::CallError>;
 
 #[derive(Clone)]
 pub struct NeovimHandler {
-    states: States,
+    instances: Instances,
 }
 
 impl NeovimHandler {
-    async fn request(&self, arg: &impl Action<Resp = Value>) -> Result<Value, Value> {
-        arg.run(&self.states).await.map_err(|_| Value::Nil)
+    async fn request(
+        &self,
+        nvim: &Neovim<NvimWtr>,
+        states: &States,
+        arg: &impl Action<Resp = Value>,
+    ) -> Result<Value, Value> {
+        match arg.run(states).await {
+            Ok(resp) => Ok(resp),
+            Err(e) => {
+                e.notify(nvim).await.ok();
+                Err(e.to_value())
+            }
+        }
     }
 
-    async fn notify(&self, arg: &impl Action<Resp = ()>) {
-        arg.run(&self.states).await.ok();
+    async fn notify(&self, nvim: &Neovim<NvimWtr>, states: &States, arg: &impl Action<Resp = ()>) {
+        if let Err(e) = arg.run(states).await {
+            e.notify(nvim).await.ok();
+        }
     }
 }
 
 impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
     fn new() -> Self {
         Self {
-            states: Default::default(),
+            instances: Default::default(),
         }
     }
 
@@ -41,8 +68,32 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
         &self,
         name: &str,
         mut args: RpcArgs,
-        _neovim: Neovim<NvimWtr>,
+        nvim: Neovim<NvimWtr>,
     ) -> Result<Value, Value> {
+        let Some(id) = args.next_i64() else {
+            return Ok(Value::Nil);
+        };
+        let id = InstanceId::from(id);
+
+        match name {
+            "create_instance" => {
+                self.instances.create(id).await;
+                return Ok(Value::Nil);
+            }
+            "dispose_instance" => {
+                let Some(states) = self.instances.dispose(id).await else {
+                    return Ok(Value::Nil);
+                };
+                states.actions.expanded_dir.stop().await;
+                return self.request(&nvim, &states, &SaveSession).await;
+            }
+            _ => {}
+        }
+
+        let Some(states) = self.instances.get(id).await else {
+            return Ok(Value::Nil);
+        };
+
         match name {
             "get_dir" => {
                 let Some(line_idx) = args.next_i64() else {
@@ -52,7 +103,7 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
 
                 let arg = GetDir { line_idx };
 
-                self.request(&arg).await
+                self.request(&nvim, &states, &arg).await
             }
             "get_file_path" => {
                 let Some(line_idx) = args.next_i64() else {
@@ -62,13 +113,106 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
 
                 let arg = GetFilePath { line_idx };
 
-                self.request(&arg).await
+                self.request(&nvim, &states, &arg).await
+            }
+            "list_trash" => {
+                let Some(dir) = args.next_string() else {
+                    return Ok(Value::Nil);
+                };
+
+                let arg = ListTrash { dir: dir.into() };
+
+                self.request(&nvim, &states, &arg).await
+            }
+            "history_back" => {
+                let arg = HistoryBack { nvim: nvim.clone() };
+
+                self.request(&nvim, &states, &arg).await
+            }
+            "history_forward" => {
+                let arg = HistoryForward { nvim: nvim.clone() };
+
+                self.request(&nvim, &states, &arg).await
+            }
+            "list_bookmarks" => self.request(&nvim, &states, &ListBookmarks).await,
+            "get_selection" => self.request(&nvim, &states, &GetSelection).await,
+            "save_session" => self.request(&nvim, &states, &SaveSession).await,
+            "search" => {
+                let Some(dir) = args.next_string() else {
+                    return Ok(Value::Nil);
+                };
+                let Some(query) = args.next_string() else {
+                    return Ok(Value::Nil);
+                };
+                let depth = args.next_i64().unwrap_or_default().max(0) as usize;
+
+                let arg = Search {
+                    dir: dir.into(),
+                    query,
+                    depth,
+                };
+
+                self.request(&nvim, &states, &arg).await
+            }
+            "reveal_path" => {
+                let Some(dir) = args.next_string() else {
+                    return Ok(Value::Nil);
+                };
+                let Some(path) = args.next_string() else {
+                    return Ok(Value::Nil);
+                };
+
+                let arg = RevealPath {
+                    nvim: nvim.clone(),
+                    dir: dir.into(),
+                    path: path.into(),
+                };
+
+                self.request(&nvim, &states, &arg).await
+            }
+            "get_rename_targets" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return Ok(Value::Nil);
+                };
+                let line_idx = line_idx.into();
+
+                let arg = GetRenameTargets { line_idx };
+
+                self.request(&nvim, &states, &arg).await
+            }
+            "bulk_rename" => {
+                let Some(dir) = args.next_string() else {
+                    return Ok(Value::Nil);
+                };
+
+                let mut names = Vec::new();
+                while let Some(from) = args.next_string() {
+                    let Some(to) = args.next_string() else {
+                        return Ok(Value::Nil);
+                    };
+                    names.push((from, to));
+                }
+
+                let arg = BulkRename {
+                    nvim: nvim.clone(),
+                    dir: dir.into(),
+                    names,
+                };
+
+                self.request(&nvim, &states, &arg).await
             }
             _ => Ok(Value::Nil),
         }
     }
 
     async fn handle_notify(&self, name: &str, mut args: RpcArgs, nvim: Neovim<NvimWtr>) {
+        let Some(id) = args.next_i64() else {
+            return;
+        };
+        let Some(states) = self.instances.get(id.into()).await else {
+            return;
+        };
+
         match name {
             "create_entry" => {
                 let Some(line_idx) = args.next_i64() else {
@@ -80,12 +224,28 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
 
                 let arg = CreateEntry {
-                    nvim,
+                    nvim: nvim.clone(),
                     line_idx,
                     fname,
                 };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "preview" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+                let lines = args.next_i64().unwrap_or_default().max(0) as usize;
+                let bytes = args.next_i64().unwrap_or_default().max(0) as usize;
+
+                let arg = Preview {
+                    nvim: nvim.clone(),
+                    line_idx,
+                    limits: Limits { lines, bytes },
+                };
+
+                self.notify(&nvim, &states, &arg).await;
             }
             "delete_entry" => {
                 let Some(line_idx) = args.next_i64() else {
@@ -93,9 +253,57 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
                 let line_idx = line_idx.into();
 
-                let arg = DeleteEntry { nvim, line_idx };
+                let arg = DeleteEntry {
+                    nvim: nvim.clone(),
+                    line_idx,
+                    trash: false,
+                };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "trash_entry" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+
+                let arg = DeleteEntry {
+                    nvim: nvim.clone(),
+                    line_idx,
+                    trash: true,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "chmod_entry" | "chmod_entry_recursive" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+                let Some(mode) = args.next_string() else {
+                    return;
+                };
+
+                let arg = ChmodEntry {
+                    nvim: nvim.clone(),
+                    line_idx,
+                    mode,
+                    recursive: name == "chmod_entry_recursive",
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "restore_entry" => {
+                let Some(id) = args.next_string() else {
+                    return;
+                };
+
+                let arg = RestoreEntry {
+                    nvim: nvim.clone(),
+                    id: id.into(),
+                };
+
+                self.notify(&nvim, &states, &arg).await;
             }
             "rename_entry" => {
                 let Some(line_idx) = args.next_i64() else {
@@ -110,13 +318,33 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
 
                 let arg = RenameEntry {
-                    nvim,
+                    nvim: nvim.clone(),
                     line_idx,
                     dir: dir.into(),
                     path,
                 };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "add_bookmark" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+                let name = args.next_string().filter(|name| !name.is_empty());
+
+                let arg = AddBookmark { line_idx, name };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "delete_bookmark" => {
+                let Some(name) = args.next_string() else {
+                    return;
+                };
+
+                let arg = DeleteBookmark { name };
+
+                self.notify(&nvim, &states, &arg).await;
             }
             "new_filer" => {
                 let Some(dir) = args.next_string() else {
@@ -124,11 +352,11 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
 
                 let arg = NewFiler {
-                    nvim,
+                    nvim: nvim.clone(),
                     dir: dir.into(),
                 };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
             }
             "refresh" => {
                 let Some(dir) = args.next_string() else {
@@ -136,11 +364,11 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
 
                 let arg = Refresh {
-                    nvim,
+                    nvim: nvim.clone(),
                     dir: dir.into(),
                 };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
             }
             "move_to_parent" => {
                 let Some(dir) = args.next_string() else {
@@ -148,21 +376,27 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
 
                 let arg = MoveToParent {
-                    nvim,
+                    nvim: nvim.clone(),
                     dir: dir.into(),
                 };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
             }
             "open_file" => {
                 let Some(line_idx) = args.next_i64() else {
                     return;
                 };
                 let line_idx = line_idx.into();
+                let mode = args.next_string();
+                let mode = OpenMode::parse(mode.as_deref(), args.next_i64());
 
-                let arg = OpenFile { line_idx, nvim };
+                let arg = OpenFile {
+                    line_idx,
+                    nvim: nvim.clone(),
+                    mode,
+                };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
             }
             "expand_dir" => {
                 let Some(line_idx) = args.next_i64() else {
@@ -170,19 +404,265 @@ impl nvim_router::NeovimHandler<NvimWtr> for NeovimHandler {
                 };
                 let line_idx = line_idx.into();
 
-                let arg = ExpandDir { line_idx, nvim };
+                let arg = ExpandDir {
+                    line_idx,
+                    nvim: nvim.clone(),
+                };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
             }
             "open_or_expand" => {
                 let Some(line_idx) = args.next_i64() else {
                     return;
                 };
                 let line_idx = line_idx.into();
+                let mode = args.next_string();
+                let mode = OpenMode::parse(mode.as_deref(), args.next_i64());
+
+                let arg = OpenOrExpand {
+                    line_idx,
+                    nvim: nvim.clone(),
+                    mode,
+                };
 
-                let arg = OpenOrExpand { line_idx, nvim };
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "yank_entry" | "cut_entry" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+
+                let mode = if name == "cut_entry" {
+                    YankMode::Cut
+                } else {
+                    YankMode::Copy
+                };
+
+                let arg = YankEntry {
+                    nvim: nvim.clone(),
+                    line_idx,
+                    mode,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "paste_entry" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+
+                let arg = PasteEntry {
+                    nvim: nvim.clone(),
+                    line_idx,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "link_entry" | "hardlink_entry" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+                let Some(fname) = args.next_string() else {
+                    return;
+                };
+
+                let kind = if name == "hardlink_entry" {
+                    LinkKind::Hard
+                } else {
+                    LinkKind::Symbolic
+                };
+
+                let arg = CreateLink {
+                    nvim: nvim.clone(),
+                    line_idx,
+                    kind,
+                    fname,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "toggle_selection" => {
+                let Some(line_idx) = args.next_i64() else {
+                    return;
+                };
+                let line_idx = line_idx.into();
+
+                let arg = ToggleSelection {
+                    nvim: nvim.clone(),
+                    line_idx,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "select_range" => {
+                let Some(start) = args.next_i64() else {
+                    return;
+                };
+                let Some(end) = args.next_i64() else {
+                    return;
+                };
+
+                let arg = SelectRange {
+                    nvim: nvim.clone(),
+                    start: start.into(),
+                    end: end.into(),
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "select_glob" => {
+                let Some(pattern) = args.next_string() else {
+                    return;
+                };
+
+                let arg = SelectGlob {
+                    nvim: nvim.clone(),
+                    pattern,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "clear_selection" => {
+                let arg = ClearSelection { nvim: nvim.clone() };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "toggle_hidden" | "toggle_ignored" => {
+                let Some(dir) = args.next_string() else {
+                    return;
+                };
+
+                let kind = if name == "toggle_ignored" {
+                    FilterKind::Ignored
+                } else {
+                    FilterKind::Hidden
+                };
+
+                let arg = ToggleFilter {
+                    nvim: nvim.clone(),
+                    dir: dir.into(),
+                    kind,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "set_filter" => {
+                let mut filter = Filter::default();
+                while let Some(kind) = args.next_string() {
+                    match kind.as_str() {
+                        "hidden" => filter.hide_hidden = true,
+                        "ignored" => filter.hide_ignored = true,
+                        _ => {}
+                    }
+                }
+
+                let arg = SetFilter { filter };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "set_limit" => {
+                let limit = args.next_i64().unwrap_or_default();
+                let limit = usize::try_from(limit).ok().filter(|&limit| limit > 0);
+
+                let arg = SetLimit { limit };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "set_time_format" => {
+                let Some(format) = args.next_string() else {
+                    return;
+                };
+
+                let arg = SetTimeFormat { format };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "set_sort" => {
+                let sort = SortOrder::parse(std::iter::from_fn(|| args.next_string()));
+
+                let arg = SortBy {
+                    nvim: nvim.clone(),
+                    dir: None,
+                    sort,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "sort_by" => {
+                let Some(dir) = args.next_string() else {
+                    return;
+                };
+                let sort = SortOrder::parse(std::iter::from_fn(|| args.next_string()));
+
+                let arg = SortBy {
+                    nvim: nvim.clone(),
+                    dir: Some(dir.into()),
+                    sort,
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "update_git_status" => {
+                let Some(path) = args.next_string() else {
+                    return;
+                };
+
+                let arg = UpdateGitStatus {
+                    nvim: nvim.clone(),
+                    path: path.into(),
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "reveal_match" => {
+                let Some(dir) = args.next_string() else {
+                    return;
+                };
+                let Some(path) = args.next_string() else {
+                    return;
+                };
+
+                let arg = RevealMatch {
+                    nvim: nvim.clone(),
+                    dir: dir.into(),
+                    path: path.into(),
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "follow_path" => {
+                let Some(dir) = args.next_string() else {
+                    return;
+                };
+                let Some(path) = args.next_string() else {
+                    return;
+                };
+
+                let arg = FollowPath {
+                    nvim: nvim.clone(),
+                    dir: dir.into(),
+                    path: path.into(),
+                };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "redraw" => {
+                let arg = Redraw { nvim: nvim.clone() };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "undo" => {
+                let arg = Undo { nvim: nvim.clone() };
+
+                self.notify(&nvim, &states, &arg).await;
+            }
+            "redo" => {
+                let arg = Redo { nvim: nvim.clone() };
 
-                self.notify(&arg).await;
+                self.notify(&nvim, &states, &arg).await;
             }
             _ => {}
         }
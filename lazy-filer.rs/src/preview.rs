@@ -0,0 +1,130 @@
+use std::ffi::OsString;
+use std::fs::Metadata;
+use std::io::{Error as IoError, Read as _};
+use std::path::{Path, PathBuf};
+
+// Files with a NUL byte in this many leading bytes are considered binary, as git does.
+const BINARY_PROBE: usize = 8000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    // Lines of a text file, or names of a directory.
+    pub lines: usize,
+    // Bytes read from a text file.
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Content {
+    Text {
+        lines: Vec<String>,
+        truncated: bool,
+    },
+    Binary {
+        size: u64,
+    },
+    Directory(DirSummary),
+    // `target` is None for a broken link.
+    Link {
+        to: PathBuf,
+        target: Option<Box<Content>>,
+    },
+    // FIFOs, sockets and devices are never opened: reading a FIFO would block.
+    Other {
+        mode: u32,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DirSummary {
+    pub dirs: usize,
+    pub files: usize,
+    pub links: usize,
+    pub others: usize,
+    // Sum of the sizes of the regular files directly in the directory.
+    pub total_size: u64,
+    // Sorted, with a trailing slash on directories.
+    pub names: Vec<OsString>,
+    pub truncated: bool,
+}
+
+pub fn read(path: &Path, limits: Limits) -> Result<Content, IoError> {
+    let meta = path.symlink_metadata()?;
+    if !meta.is_symlink() {
+        return read_with(path, &meta, limits);
+    }
+
+    let to = std::fs::read_link(path)?;
+    let target = path
+        .metadata()
+        .and_then(|meta| read_with(path, &meta, limits))
+        .ok()
+        .map(Box::new);
+
+    Ok(Content::Link { to, target })
+}
+
+fn read_with(path: &Path, meta: &Metadata, limits: Limits) -> Result<Content, IoError> {
+    use std::os::unix::fs::MetadataExt as _;
+
+    if meta.is_dir() {
+        summarize_dir(path, limits).map(Content::Directory)
+    } else if meta.is_file() {
+        read_text(path, meta.len(), limits)
+    } else {
+        Ok(Content::Other { mode: meta.mode() })
+    }
+}
+
+fn read_text(path: &Path, size: u64, limits: Limits) -> Result<Content, IoError> {
+    let mut buf = Vec::new();
+    std::fs::File::open(path)?
+        .take(limits.bytes as u64)
+        .read_to_end(&mut buf)?;
+
+    if buf[..buf.len().min(BINARY_PROBE)].contains(&0) {
+        return Ok(Content::Binary { size });
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut lines: Vec<String> = text
+        .lines()
+        .take(limits.lines + 1)
+        .map(str::to_string)
+        .collect();
+
+    let truncated = lines.len() > limits.lines || size > buf.len() as u64;
+    lines.truncate(limits.lines);
+
+    Ok(Content::Text { lines, truncated })
+}
+
+fn summarize_dir(path: &Path, limits: Limits) -> Result<DirSummary, IoError> {
+    let mut summary = DirSummary::default();
+
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let mut name = entry.file_name();
+
+        if file_type.is_symlink() {
+            summary.links += 1;
+        } else if file_type.is_dir() {
+            summary.dirs += 1;
+            name.push("/");
+        } else if file_type.is_file() {
+            summary.files += 1;
+            summary.total_size += entry.metadata().map_or(0, |meta| meta.len());
+        } else {
+            summary.others += 1;
+        }
+
+        summary.names.push(name);
+    }
+
+    summary.names.sort_unstable();
+    summary.truncated = summary.names.len() > limits.lines;
+    summary.names.truncate(limits.lines);
+
+    Ok(summary)
+}
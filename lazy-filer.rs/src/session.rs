@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::fs::DirBuilder;
+use std::io::{Error as IoError, ErrorKind};
+use std::os::unix::ffi::OsStrExt as _;
+use std::os::unix::fs::DirBuilderExt as _;
+use std::path::{Path, PathBuf};
+
+// Expanded directories under `root` saved by a previous session. Paths that are no longer
+// directories are dropped.
+pub fn load(root: &Path) -> Result<BTreeSet<PathBuf>, IoError> {
+    let Some(file) = state_file(root) else {
+        return Ok(BTreeSet::new());
+    };
+
+    let content = match std::fs::read(file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        Err(e) => return Err(e),
+    };
+
+    // The first line names the root, as another root may hash to the same file.
+    let mut lines = content.split(|&b| b == b'\n');
+    if lines.next() != Some(root.as_os_str().as_bytes()) {
+        return Ok(BTreeSet::new());
+    }
+
+    let dirs = lines
+        .filter(|line| !line.is_empty())
+        .map(|line| Path::new(OsStr::from_bytes(line)))
+        .filter(|dir| dir.starts_with(root) && dir.is_dir())
+        .map(Path::to_path_buf)
+        .collect();
+
+    Ok(dirs)
+}
+
+pub fn save(root: &Path, expanded_dir: &BTreeSet<PathBuf>) -> Result<(), IoError> {
+    let Some(file) = state_file(root) else {
+        return Ok(());
+    };
+
+    let root_bytes = root.as_os_str().as_bytes();
+    if root_bytes.contains(&b'\n') {
+        return Ok(());
+    }
+
+    let mut content = root_bytes.to_vec();
+    content.push(b'\n');
+    for dir in expanded_dir.iter().filter(|dir| dir.starts_with(root)) {
+        let bytes = dir.as_os_str().as_bytes();
+        if bytes.contains(&b'\n') {
+            continue;
+        }
+        content.extend_from_slice(bytes);
+        content.push(b'\n');
+    }
+
+    write_atomic(&file, &content)
+}
+
+// Written aside and renamed, so that a concurrent load never sees half a file.
+pub fn write_atomic(file: &Path, content: &[u8]) -> Result<(), IoError> {
+    if let Some(parent) = file.parent() {
+        let mut builder = DirBuilder::new();
+        builder.recursive(true).mode(0o700);
+        builder.create(parent)?;
+    }
+
+    let mut tmp = file.to_path_buf().into_os_string();
+    tmp.push(format!(".{}", std::process::id()));
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, file)
+}
+
+// $XDG_STATE_HOME/lazy-filer, or ~/.local/state/lazy-filer.
+pub fn state_dir() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/state")))?;
+
+    Some(state_home.join("lazy-filer"))
+}
+
+// Named after a hash of the root, as the root itself may be longer than a file name can be.
+fn state_file(root: &Path) -> Option<PathBuf> {
+    let name = format!("{:016x}", fnv1a(root.as_os_str().as_bytes()));
+    Some(state_dir()?.join("expanded").join(name))
+}
+
+// 64-bit FNV-1a. Unlike `DefaultHasher`, it is the same across Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
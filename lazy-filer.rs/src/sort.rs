@@ -0,0 +1,174 @@
+use crate::fs::Stat;
+
+use std::cmp::Reverse;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Name,
+    Natural,
+    CaseInsensitive,
+    Extension,
+    // Largest first.
+    Size,
+    // Newest first.
+    Mtime,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub dirs_first: bool,
+    pub reverse: bool,
+}
+
+// An entry as it is sorted. `stat` is the one cached when the entry was read, that of the
+// target for a symlink, so sorting never touches the filesystem.
+#[derive(Debug, Clone, Copy)]
+pub struct Entry<'a> {
+    pub path: &'a Path,
+    pub is_dir: bool,
+    pub stat: Option<&'a Stat>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Key {
+    group: u8,
+    primary: Primary,
+    fname: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Primary {
+    None,
+    Bytes(Vec<u8>),
+    Natural(Vec<Chunk>),
+    Desc(Reverse<i128>),
+}
+
+// Digit runs compare by value: shorter runs (after leading zeros) are smaller.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Chunk {
+    Num(usize, Vec<u8>),
+    Text(Vec<u8>),
+}
+
+impl SortKey {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "name" => Some(Self::Name),
+            "natural" => Some(Self::Natural),
+            "case_insensitive" => Some(Self::CaseInsensitive),
+            "extension" => Some(Self::Extension),
+            "size" => Some(Self::Size),
+            "mtime" => Some(Self::Mtime),
+            _ => None,
+        }
+    }
+}
+
+impl SortOrder {
+    // Reads `{key} [dirs_first] [reverse]`; unknown words are ignored.
+    pub fn parse(words: impl IntoIterator<Item = String>) -> Self {
+        let mut order = Self::default();
+        for word in words {
+            match word.as_str() {
+                "dirs_first" => order.dirs_first = true,
+                "reverse" => order.reverse = true,
+                key => {
+                    if let Some(key) = SortKey::parse(key) {
+                        order.key = key;
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    pub fn key(&self, entry: Entry<'_>) -> Key {
+        let Entry { path, is_dir, stat } = entry;
+        let fname = path.file_name().unwrap_or_default();
+
+        let group = if self.dirs_first && !is_dir { 1 } else { 0 };
+
+        let primary = match self.key {
+            SortKey::Name => Primary::None,
+            SortKey::Natural => Primary::Natural(natural_chunks(fname.as_bytes())),
+            SortKey::CaseInsensitive => Primary::Bytes(fname.as_bytes().to_ascii_lowercase()),
+            SortKey::Extension => Primary::Bytes(extension(fname).to_ascii_lowercase()),
+            SortKey::Size => {
+                let size = stat.map(|stat| stat.size).unwrap_or_default();
+                Primary::Desc(Reverse(i128::from(size)))
+            }
+            SortKey::Mtime => {
+                let mtime = stat
+                    .map(|stat| {
+                        i128::from(stat.mtime) * 1_000_000_000 + i128::from(stat.mtime_nsec)
+                    })
+                    .unwrap_or_default();
+                Primary::Desc(Reverse(mtime))
+            }
+        };
+
+        Key {
+            group,
+            primary,
+            fname: fname.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn sort<T>(&self, entries: &mut [T], key: impl Fn(&T) -> Entry<'_>) {
+        entries.sort_by_cached_key(|entry| self.key(key(entry)));
+
+        if self.reverse {
+            // Directories stay in front; only the order within each group is reversed.
+            entries.reverse();
+            entries.sort_by_key(|entry| self.dirs_first && !key(entry).is_dir);
+        }
+    }
+
+    pub fn is_before(&self, this: Entry<'_>, that: Entry<'_>) -> bool {
+        let this = self.key(this);
+        let that = self.key(that);
+
+        if self.reverse && this.group == that.group {
+            this > that
+        } else {
+            this < that
+        }
+    }
+}
+
+fn extension(fname: &OsStr) -> &[u8] {
+    let path: &Path = fname.as_ref();
+    path.extension().unwrap_or_default().as_bytes()
+}
+
+fn natural_chunks(fname: &[u8]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+
+    let mut rest = fname;
+    while let Some(&first) = rest.first() {
+        let is_digit = first.is_ascii_digit();
+        let len = rest
+            .iter()
+            .position(|b| b.is_ascii_digit() != is_digit)
+            .unwrap_or(rest.len());
+        let (chunk, tail) = rest.split_at(len);
+
+        if is_digit {
+            let start = chunk.iter().position(|&b| b != b'0').unwrap_or(chunk.len());
+            let digits = &chunk[start..];
+            chunks.push(Chunk::Num(digits.len(), digits.to_vec()));
+        } else {
+            chunks.push(Chunk::Text(chunk.to_vec()));
+        }
+
+        rest = tail;
+    }
+
+    chunks
+}
@@ -1,8 +1,46 @@
-use crate::actions::states::States as ActionStates;
+use crate::actions::states::{InstanceId, States as ActionStates};
 use crate::fs::RootFile;
 
+use std::collections::HashMap;
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
 #[derive(Debug, Clone, Default)]
 pub struct States {
     pub root_file: RootFile,
     pub actions: ActionStates,
 }
+
+// Filer instances, each with its own rendered lines, expanded directories and view.
+// They share the cache of the filesystem.
+#[derive(Debug, Clone, Default)]
+pub struct Instances {
+    root_file: RootFile,
+    actions: Arc<Mutex<HashMap<InstanceId, ActionStates>>>,
+}
+
+impl Instances {
+    fn states(&self, actions: ActionStates) -> States {
+        States {
+            root_file: self.root_file.clone(),
+            actions,
+        }
+    }
+
+    pub async fn create(&self, id: InstanceId) -> States {
+        let mut lock = self.actions.lock().await;
+        let actions = lock.entry(id).or_insert_with(|| ActionStates::new(id));
+        self.states(actions.clone())
+    }
+
+    pub async fn get(&self, id: InstanceId) -> Option<States> {
+        let lock = self.actions.lock().await;
+        lock.get(&id).map(|actions| self.states(actions.clone()))
+    }
+
+    pub async fn dispose(&self, id: InstanceId) -> Option<States> {
+        let mut lock = self.actions.lock().await;
+        lock.remove(&id).map(|actions| self.states(actions))
+    }
+}
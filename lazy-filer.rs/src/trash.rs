@@ -0,0 +1,304 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::{Error as IoError, ErrorKind, Write as _};
+use std::os::unix::ffi::{OsStrExt as _, OsStringExt as _};
+use std::os::unix::fs::{DirBuilderExt as _, MetadataExt as _, OpenOptionsExt as _};
+use std::path::{Path, PathBuf};
+
+const INFO_EXT: &str = "trashinfo";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedFile {
+    trash_dir: PathBuf,
+    name: OsString,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrashEntry {
+    pub file: TrashedFile,
+    pub original: PathBuf,
+    pub deleted_at: String,
+}
+
+impl TrashedFile {
+    pub fn from_info_path(info: &Path) -> Option<Self> {
+        if info.extension() != Some(OsStr::new(INFO_EXT)) {
+            return None;
+        }
+
+        let name = info.file_stem()?;
+        let trash_dir = info.parent()?.parent()?;
+
+        Some(Self {
+            trash_dir: trash_dir.to_path_buf(),
+            name: name.to_os_string(),
+        })
+    }
+
+    pub fn file_path(&self) -> PathBuf {
+        self.trash_dir.join("files").join(&self.name)
+    }
+
+    pub fn info_path(&self) -> PathBuf {
+        let mut name = self.name.clone();
+        name.push(".");
+        name.push(INFO_EXT);
+        self.trash_dir.join("info").join(name)
+    }
+
+    // Per-mount trash directories store `Path=` relative to the mount point.
+    fn topdir(&self) -> Option<PathBuf> {
+        let home = home_trash()?;
+        if self.trash_dir == home {
+            return None;
+        }
+
+        let fname = self.trash_dir.file_name()?;
+        let parent = self.trash_dir.parent()?;
+        if fname.as_bytes().starts_with(b".Trash-") {
+            Some(parent.to_path_buf())
+        } else {
+            parent.parent().map(Path::to_path_buf)
+        }
+    }
+
+    pub fn read_info(&self) -> Result<TrashEntry, IoError> {
+        let content = std::fs::read_to_string(self.info_path())?;
+
+        let mut original = None;
+        let mut deleted_at = String::new();
+
+        for line in content.lines() {
+            if let Some(path) = line.strip_prefix("Path=") {
+                let path = PathBuf::from(OsString::from_vec(percent_decode(path)));
+                original = Some(match self.topdir() {
+                    Some(topdir) if path.is_relative() => topdir.join(path),
+                    _ => path,
+                });
+            } else if let Some(date) = line.strip_prefix("DeletionDate=") {
+                deleted_at = date.to_string();
+            }
+        }
+
+        let Some(original) = original else {
+            return Err(ErrorKind::InvalidData.into());
+        };
+
+        Ok(TrashEntry {
+            file: self.clone(),
+            original,
+            deleted_at,
+        })
+    }
+
+    pub fn restore(&self) -> Result<PathBuf, IoError> {
+        let TrashEntry { original, .. } = self.read_info()?;
+
+        if original.symlink_metadata().is_ok() {
+            return Err(ErrorKind::AlreadyExists.into());
+        }
+        if let Some(parent) = original.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::rename(self.file_path(), &original)?;
+        std::fs::remove_file(self.info_path())?;
+
+        Ok(original)
+    }
+}
+
+pub fn trash(path: &Path) -> Result<TrashedFile, IoError> {
+    let dev = path.symlink_metadata()?.dev();
+
+    let (trash_dir, info_path) = match home_trash() {
+        Some(home) if ensure_dir(&home).is_ok() && home.metadata()?.dev() == dev => {
+            (home, path.to_path_buf())
+        }
+        _ => {
+            let topdir = topdir(path, dev)?;
+            let trash_dir = topdir_trash(&topdir)?;
+            let relative = path.strip_prefix(&topdir).unwrap_or(path).to_path_buf();
+            (trash_dir, relative)
+        }
+    };
+
+    ensure_dir(&trash_dir.join("files"))?;
+    ensure_dir(&trash_dir.join("info"))?;
+
+    let fname = path.file_name().unwrap_or_default();
+    let file = reserve(&trash_dir, fname, &info_path)?;
+
+    if let Err(e) = std::fs::rename(path, file.file_path()) {
+        std::fs::remove_file(file.info_path()).ok();
+        return Err(e);
+    }
+
+    Ok(file)
+}
+
+pub fn trash_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<_> = home_trash().into_iter().collect();
+
+    if let Ok(meta) = dir.metadata()
+        && let Ok(topdir) = topdir(dir, meta.dev())
+    {
+        let uid = nix::unistd::getuid().as_raw();
+        for trash_dir in [
+            topdir.join(".Trash").join(uid.to_string()),
+            topdir.join(format!(".Trash-{uid}")),
+        ] {
+            if trash_dir.is_dir() && !dirs.contains(&trash_dir) {
+                dirs.push(trash_dir);
+            }
+        }
+    }
+
+    dirs
+}
+
+pub fn list(trash_dirs: &[PathBuf]) -> Vec<TrashEntry> {
+    let mut entries = Vec::new();
+
+    for trash_dir in trash_dirs {
+        let Ok(infos) = std::fs::read_dir(trash_dir.join("info")) else {
+            continue;
+        };
+
+        for info in infos.flatten() {
+            if let Some(file) = TrashedFile::from_info_path(&info.path())
+                && let Ok(entry) = file.read_info()
+            {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries.sort_unstable_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    entries
+}
+
+fn home_trash() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+
+    Some(data_home.join("Trash"))
+}
+
+fn ensure_dir(dir: &Path) -> Result<(), IoError> {
+    if dir.is_dir() {
+        return Ok(());
+    }
+
+    let mut builder = DirBuilder::new();
+    builder.recursive(true).mode(0o700);
+    builder.create(dir)
+}
+
+fn topdir(path: &Path, dev: u64) -> Result<PathBuf, IoError> {
+    let mut topdir = path.parent().unwrap_or(path);
+
+    while let Some(parent) = topdir.parent() {
+        if parent.metadata()?.dev() != dev {
+            break;
+        }
+        topdir = parent;
+    }
+
+    Ok(topdir.to_path_buf())
+}
+
+fn topdir_trash(topdir: &Path) -> Result<PathBuf, IoError> {
+    let uid = nix::unistd::getuid().as_raw();
+
+    // An administrator-created `$topdir/.Trash` is only usable if it is a sticky directory and
+    // not a symlink.
+    let shared = topdir.join(".Trash");
+    if let Ok(meta) = shared.symlink_metadata()
+        && meta.is_dir()
+        && meta.mode() & 0o1000 != 0
+    {
+        let trash_dir = shared.join(uid.to_string());
+        if ensure_dir(&trash_dir).is_ok() {
+            return Ok(trash_dir);
+        }
+    }
+
+    let trash_dir = topdir.join(format!(".Trash-{uid}"));
+    ensure_dir(&trash_dir)?;
+    Ok(trash_dir)
+}
+
+fn reserve(trash_dir: &Path, fname: &OsStr, original: &Path) -> Result<TrashedFile, IoError> {
+    let deleted_at = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S");
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={deleted_at}\n",
+        percent_encode(original.as_os_str().as_bytes())
+    );
+
+    for n in 0.. {
+        let mut name = fname.to_os_string();
+        if n > 0 {
+            name.push(format!(".{n}"));
+        }
+
+        let file = TrashedFile {
+            trash_dir: trash_dir.to_path_buf(),
+            name,
+        };
+        if file.file_path().symlink_metadata().is_ok() {
+            continue;
+        }
+
+        let mut opts = OpenOptions::new();
+        opts.write(true).create_new(true).mode(0o600);
+        match opts.open(file.info_path()) {
+            Ok(mut info) => {
+                // A partial info file would keep the name reserved for good.
+                if let Err(e) = info.write_all(content.as_bytes()) {
+                    std::fs::remove_file(file.info_path()).ok();
+                    return Err(e);
+                }
+                return Ok(file);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    unreachable!()
+}
+
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut ret = String::with_capacity(bytes.len());
+    for &b in bytes {
+        if b.is_ascii_alphanumeric() || b"/-_.!~*'()".contains(&b) {
+            ret.push(b as char);
+        } else {
+            ret.push_str(&format!("%{b:02X}"));
+        }
+    }
+    ret
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut ret = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = s.get(i + 1..i + 3)
+            && let Ok(b) = u8::from_str_radix(hex, 16)
+        {
+            ret.push(b);
+            i += 3;
+        } else {
+            ret.push(bytes[i]);
+            i += 1;
+        }
+    }
+    ret
+}
@@ -0,0 +1,134 @@
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Filter {
+    pub hide_hidden: bool,
+    pub hide_ignored: bool,
+}
+
+impl Filter {
+    pub fn matcher(self) -> Matcher {
+        Matcher {
+            filter: self,
+            dirs: BTreeMap::new(),
+            global: None,
+        }
+    }
+}
+
+// Ignore files found in a single directory. `.gitignore` and `.git/info/exclude` only count
+// inside a git repository, whereas `.ignore` always does.
+#[derive(Debug)]
+struct DirIgnores {
+    ignore: Option<Gitignore>,
+    git: Vec<Gitignore>,
+    is_repo_root: bool,
+}
+
+// Matchers are loaded lazily and cached for the lifetime of a single render.
+#[derive(Debug)]
+pub struct Matcher {
+    filter: Filter,
+    dirs: BTreeMap<PathBuf, DirIgnores>,
+    global: Option<Gitignore>,
+}
+
+impl Matcher {
+    pub fn is_visible(&mut self, path: &Path, is_dir: bool) -> bool {
+        if self.filter.hide_hidden && is_hidden(path) {
+            return false;
+        }
+        if self.filter.hide_ignored && self.is_ignored(path, is_dir) {
+            return false;
+        }
+        true
+    }
+
+    fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        if path.file_name() == Some(OsStr::new(".git")) {
+            return true;
+        }
+
+        let mut ancestors = Vec::new();
+        let mut in_repo = false;
+        for dir in path.ancestors().skip(1) {
+            let ignores = self
+                .dirs
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| DirIgnores::load(dir));
+            ancestors.push(dir);
+
+            if ignores.is_repo_root {
+                in_repo = true;
+                break;
+            }
+        }
+
+        // The deepest ignore file that says anything about the path wins.
+        for dir in ancestors {
+            let Some(ignores) = self.dirs.get(dir) else {
+                continue;
+            };
+
+            let git = if in_repo { &ignores.git[..] } else { &[] };
+            for gitignore in ignores.ignore.iter().chain(git) {
+                match gitignore.matched(path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+        }
+
+        if !in_repo {
+            return false;
+        }
+
+        let global = self.global.get_or_insert_with(|| Gitignore::global().0);
+        global.matched(path, is_dir).is_ignore()
+    }
+}
+
+impl DirIgnores {
+    fn load(dir: &Path) -> Self {
+        let git_dir = dir.join(".git");
+        let is_repo_root = git_dir.exists();
+
+        let mut git: Vec<_> = read_ignore(dir, &dir.join(".gitignore"))
+            .into_iter()
+            .collect();
+        if is_repo_root {
+            git.extend(read_ignore(dir, &git_dir.join("info").join("exclude")));
+        }
+
+        Self {
+            ignore: read_ignore(dir, &dir.join(".ignore")),
+            git,
+            is_repo_root,
+        }
+    }
+}
+
+fn read_ignore(root: &Path, file: &Path) -> Option<Gitignore> {
+    if !file.is_file() {
+        return None;
+    }
+
+    let mut builder = GitignoreBuilder::new(root);
+    builder.add(file);
+    builder
+        .build()
+        .ok()
+        .filter(|gitignore| !gitignore.is_empty())
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|fname| fname.as_bytes().starts_with(b"."))
+}
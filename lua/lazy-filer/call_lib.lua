@@ -3,57 +3,278 @@ local rpc = require("lazy-filer.namespace").rpc
 
 local ui = states.ui
 
+local api = vim.api
+
+local function sort_args()
+    local sort = states.sort.get() or states.opts.sort
+    local args = { sort.by }
+    if sort.dirs_first then table.insert(args, "dirs_first") end
+    if sort.reverse then table.insert(args, "reverse") end
+    return args
+end
+
+-- Every tabpage has its own filer instance, keyed by the tabpage handle and created on the
+-- first call from that tabpage. The view options are sent along with it; afterwards the filter
+-- is owned by the Rust side, and the sort order is re-sent whenever it changes.
+local function instance()
+    local id = api.nvim_get_current_tabpage()
+    if states.instances[id] then return id end
+
+    states.instances[id] = { stale = false }
+    pcall(rpc.request, "create_instance", id)
+    states.sort.set(vim.deepcopy(states.opts.sort))
+
+    local kinds = {}
+    if states.opts.hide_hidden then table.insert(kinds, "hidden") end
+    if states.opts.hide_ignored then table.insert(kinds, "ignored") end
+    rpc.notify("set_filter", id, unpack(kinds))
+
+    rpc.notify("set_sort", id, unpack(sort_args()))
+    rpc.notify("set_limit", id, states.opts.max_entries)
+    if states.opts.time_format then
+        rpc.notify("set_time_format", id, states.opts.time_format)
+    end
+
+    return id
+end
+
+local function notify(name, ...)
+    rpc.notify(name, instance(), ...)
+end
+
+-- Errors are reported to the user by the Rust side, so a failed request is just nil here.
+local function request_on(id, name, ...)
+    local ok, ret = pcall(rpc.request, name, id, ...)
+    if not ok or ret == vim.NIL then return nil end
+    return ret
+end
+
+local function request(name, ...)
+    return request_on(instance(), name, ...)
+end
+
 return {
+    add_bookmark = function(line_idx, name)
+        notify("add_bookmark", line_idx - 1, name or "")
+    end,
+
+    bulk_rename = function(dir, from, to)
+        local args = {}
+        for i, name in ipairs(from) do
+            table.insert(args, name)
+            table.insert(args, to[i])
+        end
+        return request("bulk_rename", dir, unpack(args)) ~= nil
+    end,
+
+    chmod_entry = function(line_idx, mode, recursive)
+        if line_idx == 1 then return end
+        if recursive then
+            notify("chmod_entry_recursive", line_idx - 1, mode)
+        else
+            notify("chmod_entry", line_idx - 1, mode)
+        end
+    end,
+
+    clear_selection = function()
+        notify("clear_selection")
+    end,
+
     create_entry = function(dir_line_idx, fname)
-        rpc.notify("create_entry", dir_line_idx, fname)
+        notify("create_entry", dir_line_idx, fname)
     end,
 
-    delete_entry = function(dir_line_idx)
+    cut_entry = function(line_idx)
+        if line_idx == 1 then return end
+        notify("cut_entry", line_idx - 1)
+    end,
+
+    delete_bookmark = function(name)
+        notify("delete_bookmark", name)
+    end,
+
+    delete_entry = function(dir_line_idx, trash)
         if dir_line_idx == 0 then return end
-        rpc.notify("delete_entry", dir_line_idx)
+        if trash then
+            notify("trash_entry", dir_line_idx)
+        else
+            notify("delete_entry", dir_line_idx)
+        end
     end,
 
     expand_dir = function(line_idx)
         if line_idx == 1 then return end
-        rpc.notify("expand_dir", line_idx - 1)
+        notify("expand_dir", line_idx - 1)
+    end,
+
+    follow_path = function(cwd, path)
+        notify("follow_path", cwd, path)
     end,
 
     get_dir = function(line_idx)
-        local dir = rpc.request("get_dir", line_idx - 1)
-        if dir == vim.NIL then dir = nil end
+        local dir = request("get_dir", line_idx - 1)
         return { name = dir, idx = line_idx - 1 }
     end,
 
     get_file_path = function(line_idx)
-        local file = rpc.request("get_file_path", line_idx - 1)
-        if file == vim.NIL then file = nil end
+        local file = request("get_file_path", line_idx - 1)
         return { name = file, idx = line_idx - 1 }
     end,
 
+    get_rename_targets = function(line_idx)
+        return request("get_rename_targets", line_idx - 1)
+    end,
+
+    get_selection = function()
+        return request("get_selection") or {}
+    end,
+
+    -- An empty fname names the links after their targets.
+    link_entry = function(line_idx, hard, fname)
+        if hard then
+            notify("hardlink_entry", line_idx - 1, fname)
+        else
+            notify("link_entry", line_idx - 1, fname)
+        end
+    end,
+
+    -- Both return the root displayed afterwards, or nil if the history is empty.
+    history_back = function()
+        return request("history_back")
+    end,
+
+    history_forward = function()
+        return request("history_forward")
+    end,
+
+    list_bookmarks = function()
+        return request("list_bookmarks") or {}
+    end,
+
+    list_trash = function(cwd)
+        return request("list_trash", cwd) or {}
+    end,
+
     move_to_parent = function(cwd)
-        rpc.notify("move_to_parent", cwd)
+        notify("move_to_parent", cwd)
     end,
 
     new_filer = function(cwd)
-        rpc.notify("new_filer", cwd)
+        notify("new_filer", cwd)
     end,
 
-    open_file = function(line_idx)
+    -- mode: "current" (default), "split", "vsplit", "tab", or "window" with a window id.
+    open_file = function(line_idx, mode, win)
         if line_idx == 1 then return end
-        rpc.notify("open_file", line_idx - 1)
+        notify("open_file", line_idx - 1, mode or "current", win)
     end,
 
-    open_or_expand = function(line_idx)
+    open_or_expand = function(line_idx, mode, win)
         if line_idx == 1 then return end
-        rpc.notify("open_or_expand", line_idx - 1)
+        notify("open_or_expand", line_idx - 1, mode or "current", win)
+    end,
+
+    paste_entry = function(line_idx)
+        notify("paste_entry", line_idx - 1)
+    end,
+
+    -- The preview comes back through call_lua.show_preview.
+    preview = function(line_idx)
+        local opts = states.opts.preview
+        notify("preview", line_idx - 1, opts.max_lines, opts.max_bytes)
+    end,
+
+    redo = function()
+        notify("redo")
+    end,
+
+    redraw = function()
+        notify("redraw")
     end,
 
     refresh = function(cwd)
-        rpc.notify("refresh", cwd)
+        notify("refresh", cwd)
+    end,
+
+    restore_entry = function(id)
+        notify("restore_entry", id)
     end,
 
     rename_entry = function(dir_line_idx, new_path, cwd)
         if dir_line_idx == 0 then return end
-        rpc.notify("rename_entry", dir_line_idx, cwd, new_path)
+        notify("rename_entry", dir_line_idx, cwd, new_path)
+    end,
+
+    -- Saves the expanded directories of every instance.
+    save_session = function()
+        for id, _ in pairs(states.instances) do
+            request_on(id, "save_session")
+        end
+    end,
+
+    -- Disposes the instances whose tabpage has been closed.
+    dispose_closed = function()
+        local alive = {}
+        for _, id in ipairs(api.nvim_list_tabpages()) do
+            alive[id] = true
+        end
+
+        for id, _ in pairs(states.instances) do
+            if not alive[id] then
+                states.instances[id] = nil
+                request_on(id, "dispose_instance")
+            end
+        end
+    end,
+
+    search = function(cwd, query, depth)
+        return request("search", cwd, query, depth) or {}
+    end,
+
+    reveal_match = function(cwd, path)
+        notify("reveal_match", cwd, path)
+    end,
+
+    -- Returns { root = <displayed root>, line = <line of the path, or nil> }.
+    reveal_path = function(cwd, path)
+        return request("reveal_path", cwd, path)
+    end,
+
+    select_glob = function(pattern)
+        notify("select_glob", pattern)
+    end,
+
+    select_range = function(start_line_idx, end_line_idx)
+        notify("select_range", start_line_idx - 1, end_line_idx - 1)
+    end,
+
+    update_git_status = function(path)
+        notify("update_git_status", path)
+    end,
+
+    sort_by = function(cwd)
+        notify("sort_by", cwd, unpack(sort_args()))
+    end,
+
+    toggle_hidden = function(cwd)
+        notify("toggle_hidden", cwd)
+    end,
+
+    toggle_ignored = function(cwd)
+        notify("toggle_ignored", cwd)
+    end,
+
+    toggle_selection = function(line_idx)
+        if line_idx == 1 then return end
+        notify("toggle_selection", line_idx - 1)
+    end,
+
+    undo = function()
+        notify("undo")
+    end,
+
+    yank_entry = function(line_idx)
+        if line_idx == 1 then return end
+        notify("yank_entry", line_idx - 1)
     end,
 }
@@ -1,19 +1,78 @@
+local file_ops = require("lazy-filer.file_ops")
 local filer_api = require("lazy-filer.filer_win")
 local myui = require("my-ui")
+local states = require("lazy-filer.states")
+local subwin = require("lazy-filer.subwin")
+local ui = states.ui
 
 local api = vim.api
 
+-- Updates from the Rust side may come for an instance in another tabpage.
+local function is_current(instance)
+    return instance == api.nvim_get_current_tabpage()
+end
+
 return {
     focus_on_last_active_win = function()
         myui.close_all()
         myui.focus_on_last_active_win()
     end,
 
-    open_filer_win = function()
+    -- A window showing the file, looked for in the current tabpage first, then in the others
+    -- if all_tabs is set. Floating windows are skipped.
+    find_win = function(path, all_tabs)
+        local tabs = { api.nvim_get_current_tabpage() }
+        if all_tabs then vim.list_extend(tabs, api.nvim_list_tabpages()) end
+
+        for _, tab in ipairs(tabs) do
+            for _, win in ipairs(api.nvim_tabpage_list_wins(tab)) do
+                local buf = api.nvim_win_get_buf(win)
+                if api.nvim_win_get_config(win).relative == "" and api.nvim_buf_get_name(buf) == path then
+                    return win
+                end
+            end
+        end
+        return nil
+    end,
+
+    open_filer_win = function(instance)
+        if not is_current(instance) then return end
         filer_api.open_win()
     end,
 
-    update_filer_buf = function(start_line, end_line, items)
+    update_filer_buf = function(instance, start_line, end_line, items)
+        if not is_current(instance) then
+            if states.instances[instance] then states.instances[instance].stale = true end
+            return
+        end
         filer_api.update_buf(start_line, end_line, items)
     end,
+
+    set_cursor = function(instance, line)
+        if not is_current(instance) then return end
+        local win = ui.main.get_win()
+        if not win then return end
+        api.nvim_win_set_cursor(win, { line, 0 })
+    end,
+
+    -- The cursor line of the filer window, or nil if it is not shown.
+    show_preview = function(instance, line_idx, preview)
+        if not is_current(instance) then return end
+        subwin.preview.show(line_idx + 1, preview)
+    end,
+
+    get_cursor = function(instance)
+        if not is_current(instance) then return nil end
+        local win = ui.main.get_win()
+        if not win then return nil end
+        return api.nvim_win_get_cursor(win)[1]
+    end,
+
+    -- See lazy-filer.file_ops. Buffers and language servers follow the filer's file operations.
+    will_change_files = file_ops.will,
+    did_change_files = file_ops.did,
+
+    notify = function(msg, level)
+        vim.notify(msg, vim.log.levels[level] or vim.log.levels.ERROR)
+    end,
 }
@@ -0,0 +1,231 @@
+local states = require("lazy-filer.states")
+
+local api = vim.api
+
+local M = {}
+
+-- File operations come from the Rust side as
+-- { kind = "create" | "delete", path = ..., dir = ... } or
+-- { kind = "rename", old = ..., new = ..., dir = ... }.
+local kinds = { "create", "rename", "delete" }
+
+-- "will" .. "rename" -> "willRename", the key of the server capability.
+local function capability(phase, kind)
+    return phase .. kind:sub(1, 1):upper() .. kind:sub(2)
+end
+
+local function request_sync(client, method, params, timeout)
+    if vim.fn.has("nvim-0.11") == 1 then
+        return client:request_sync(method, params, timeout, 0)
+    end
+    return client.request_sync(method, params, timeout, 0)
+end
+
+local function notify(client, method, params)
+    if vim.fn.has("nvim-0.11") == 1 then
+        return client:notify(method, params)
+    end
+    return client.notify(method, params)
+end
+
+local function matches(filter, op)
+    if filter.scheme and filter.scheme ~= "file" then return false end
+
+    local pattern = filter.pattern
+    if pattern.matches == "file" and op.dir then return false end
+    if pattern.matches == "folder" and not op.dir then return false end
+
+    local glob = pattern.glob
+    local path = op.old or op.path
+    if pattern.options and pattern.options.ignoreCase then
+        glob, path = glob:lower(), path:lower()
+    end
+
+    local ok, lpeg = pcall(vim.glob.to_lpeg, glob)
+    return ok and lpeg:match(path) ~= nil
+end
+
+local function registered(filters, op)
+    for _, filter in ipairs(filters or {}) do
+        if matches(filter, op) then return true end
+    end
+    return false
+end
+
+-- The params of the request for the operations of a kind that the client has registered for,
+-- or nil if there is none.
+local function params_for(client, phase, kind, ops)
+    local caps = client.server_capabilities or {}
+    local opts = vim.tbl_get(caps, "workspace", "fileOperations", capability(phase, kind))
+    if not opts then return nil end
+
+    local files = {}
+    for _, op in ipairs(ops) do
+        if op.kind == kind and registered(opts.filters, op) then
+            if kind == "rename" then
+                table.insert(files, { oldUri = vim.uri_from_fname(op.old), newUri = vim.uri_from_fname(op.new) })
+            else
+                table.insert(files, { uri = vim.uri_from_fname(op.path) })
+            end
+        end
+    end
+
+    if #files == 0 then return nil end
+    return { files = files }
+end
+
+-- Buffers of the path, or of any file under it if it is a directory.
+local function bufs_under(path)
+    local bufs = {}
+    for _, buf in ipairs(api.nvim_list_bufs()) do
+        local name = api.nvim_buf_get_name(buf)
+        if vim.bo[buf].buftype == "" and (name == path or vim.startswith(name, path .. "/")) then
+            table.insert(bufs, { buf = buf, name = name })
+        end
+    end
+    return bufs
+end
+
+-- The name a buffer ends up with after the renames, made in order.
+local function renamed(name, renames)
+    for _, op in ipairs(renames) do
+        if name == op.old or vim.startswith(name, op.old .. "/") then
+            name = op.new .. name:sub(#op.old + 1)
+        end
+    end
+    return name
+end
+
+-- Renaming leaves an unloaded buffer with the old name behind, as :file does.
+local function wipe_unloaded(name)
+    for _, buf in ipairs(api.nvim_list_bufs()) do
+        if not api.nvim_buf_is_loaded(buf) and api.nvim_buf_get_name(buf) == name then
+            api.nvim_buf_delete(buf, { force = true })
+        end
+    end
+end
+
+local function is_loaded_with_name(name)
+    for _, buf in ipairs(api.nvim_list_bufs()) do
+        if api.nvim_buf_is_loaded(buf) and api.nvim_buf_get_name(buf) == name then return true end
+    end
+    return false
+end
+
+-- Buffers are moved through temporary names first, so that files that swapped names do not
+-- clash on the way (E95).
+local function retarget(renames)
+    local moves = {}
+    for _, buf in ipairs(api.nvim_list_bufs()) do
+        local name = api.nvim_buf_get_name(buf)
+        if vim.bo[buf].buftype == "" and name ~= "" then
+            local new = renamed(name, renames)
+            if new ~= name then table.insert(moves, { buf = buf, old = name, new = new }) end
+        end
+    end
+
+    for i, m in ipairs(moves) do
+        m.tmp = string.format("%s.lazy-filer-%d-%d", m.old, vim.uv.os_getpid(), i)
+        api.nvim_buf_set_name(m.buf, m.tmp)
+        wipe_unloaded(m.old)
+    end
+
+    for _, m in ipairs(moves) do
+        -- A buffer not loaded only holds the name; a loaded one, say a new file not written
+        -- yet, keeps it, and the renamed buffer goes back to its old name.
+        wipe_unloaded(m.new)
+        local ok = not is_loaded_with_name(m.new) and pcall(api.nvim_buf_set_name, m.buf, m.new)
+        if not ok then
+            pcall(api.nvim_buf_set_name, m.buf, m.old)
+            vim.notify(
+                string.format("lazy-filer: a buffer is already named %s, %s is not renamed", m.new, m.old),
+                vim.log.levels.WARN
+            )
+        end
+        wipe_unloaded(m.tmp)
+
+        -- A renamed buffer may not be written over an existing file without !. It is reloaded
+        -- to lift that, unless it has changes, which :w! then writes to the new path.
+        if api.nvim_buf_is_loaded(m.buf) and not vim.bo[m.buf].modified then
+            api.nvim_buf_call(m.buf, function() vim.cmd("silent! edit") end)
+        end
+    end
+end
+
+-- Windows showing the buffer switch to their alternate buffer, or to an empty one.
+local function replace_in_wins(buf)
+    for _, win in ipairs(vim.fn.win_findbuf(buf)) do
+        local alt = api.nvim_win_call(win, function() return vim.fn.bufnr("#") end)
+        if alt < 1 or alt == buf or not vim.bo[alt].buflisted then
+            alt = api.nvim_create_buf(true, false)
+        end
+        api.nvim_win_set_buf(win, alt)
+    end
+end
+
+-- Buffers with changes are marked rather than wiped, so that the changes are not lost.
+local function forget(path)
+    local mode = states.opts.delete_buffers
+    if not mode then return end
+
+    for _, b in ipairs(bufs_under(path)) do
+        local loaded = api.nvim_buf_is_loaded(b.buf)
+        if mode == "wipe" and not (loaded and vim.bo[b.buf].modified) then
+            replace_in_wins(b.buf)
+            api.nvim_buf_delete(b.buf, { force = true })
+        elseif loaded then
+            vim.bo[b.buf].modified = true
+            vim.b[b.buf].lazy_filer_deleted = true
+        end
+    end
+end
+
+-- Asks the language servers about the operations before they are made, and applies the
+-- workspace edits they return. Servers that do not answer in time are skipped.
+function M.will(ops)
+    local lsp = states.opts.lsp
+    if not lsp.file_operations then return end
+
+    for _, kind in ipairs(kinds) do
+        for _, client in ipairs(vim.lsp.get_clients()) do
+            local params = params_for(client, "will", kind, ops)
+            if params then
+                local method = "workspace/" .. capability("will", kind) .. "Files"
+                local resp, err = request_sync(client, method, params, lsp.timeout)
+                if resp and resp.result then
+                    vim.lsp.util.apply_workspace_edit(resp.result, client.offset_encoding)
+                elseif err or (resp and resp.err) then
+                    local msg = err or resp.err.message or vim.inspect(resp.err)
+                    vim.notify(
+                        string.format("lazy-filer: %s failed on %s: %s", client.name, method, msg),
+                        vim.log.levels.WARN
+                    )
+                end
+            end
+        end
+    end
+end
+
+-- Called once the operations are made, in the order they were made.
+function M.did(ops)
+    local renames = {}
+    for _, op in ipairs(ops) do
+        if op.kind == "rename" then
+            table.insert(renames, op)
+        elseif op.kind == "delete" then
+            forget(op.path)
+        end
+    end
+    retarget(renames)
+
+    if not states.opts.lsp.file_operations then return end
+
+    for _, kind in ipairs(kinds) do
+        for _, client in ipairs(vim.lsp.get_clients()) do
+            local params = params_for(client, "did", kind, ops)
+            if params then notify(client, "workspace/" .. capability("did", kind) .. "Files", params) end
+        end
+    end
+end
+
+return M
@@ -51,6 +51,45 @@ local function file_icon(item)
     return "\u{f29c} "
 end
 
+local git_signs = {
+    modified = "M",
+    staged = "A",
+    untracked = "?",
+    ignored = "!",
+    conflicted = "U",
+    dirty = "\u{f444}",
+}
+
+-- ls -l style columns, shown for entries whose stat(2) succeeded.
+local column_text = {
+    mode = function(item) return item.mode_str end,
+    nlink = function(item) return string.format("%3d", item.nlink) end,
+    owner = function(item) return string.format("%-8s", item.owner) end,
+    group = function(item) return string.format("%-8s", item.group) end,
+    size = function(item) return string.format("%5s", item.size_human) end,
+    mtime = function(item) return item.mtime end,
+    ctime = function(item) return item.ctime end,
+    btime = function(item) return item.btime or "-" end,
+    inode = function(item) return tostring(item.ino) end,
+}
+
+local function columns_text(item)
+    if not item.mode_str then return nil end
+
+    local texts = {}
+    for _, column in ipairs(states.opts.columns) do
+        local fn = column_text[column]
+        if fn then table.insert(texts, fn(item)) end
+    end
+    if #texts == 0 then return nil end
+    return " " .. table.concat(texts, " ")
+end
+
+local placeholder_text = {
+    loading = "loading\u{2026}",
+    timed_out = "timed out",
+}
+
 local function build_buf_lines(items)
     local lines = {}
     local highlights = {}
@@ -61,6 +100,29 @@ local function build_buf_lines(items)
             indent = "    " .. string.rep("\u{eb10}   ", item.level - 1)
         end
 
+        if item.placeholder then
+            local text = placeholder_text[item.placeholder] or item.placeholder
+            if item.placeholder == "more" then
+                text = string.format("\u{2026} %d more entries", item.rest)
+            end
+            table.insert(lines, indent .. text)
+
+            local indent_len = string.len(indent)
+            table.insert(highlights, {
+                line = i,
+                hl = "indent",
+                start_col = 0,
+                end_col = indent_len,
+            })
+            table.insert(highlights, {
+                line = i,
+                hl = "placeholder",
+                start_col = indent_len,
+                end_col = indent_len + string.len(text),
+            })
+            goto continue
+        end
+
         local fname = file_icon(item) .. item.fname
 
         local line = indent .. fname
@@ -87,6 +149,12 @@ local function build_buf_lines(items)
             insert_hl("empty_line", {})
         end
 
+        if item.selected then
+            insert_hl("selected", {})
+        elseif item.current then
+            insert_hl("current", {})
+        end
+
         local indent_len = string.len(indent)
         insert_hl("indent", {
             start_col = 0,
@@ -117,12 +185,27 @@ local function build_buf_lines(items)
             end_col = indent_len + fname_len,
         })
 
+        if item.git and git_signs[item.git] then
+            insert_hl("git_" .. item.git, {
+                virt_text = git_signs[item.git] .. " ",
+                pos = "eol",
+            })
+        end
+
         local metadata = metadata_text(item)
         insert_hl("metadata", {
             virt_text = metadata,
             pos = "eol",
         })
 
+        local columns = columns_text(item)
+        if columns then
+            insert_hl("metadata", {
+                virt_text = columns,
+                pos = "eol",
+            })
+        end
+
         if item.is_link and item.link_to and item.link_to ~= vim.NIL then
             local link_text = " \u{f44c} " .. item.link_to
             insert_hl("link_to", {
@@ -130,6 +213,8 @@ local function build_buf_lines(items)
                 pos = "eol",
             })
         end
+
+        ::continue::
     end
 
     return lines, highlights
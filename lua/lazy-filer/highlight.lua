@@ -14,6 +14,15 @@ local default_hl = {
     other_file = { link = "Comment" },
     link_to = { link = "Comment" },
     indent = { link = "Comment" },
+    selected = { link = "Visual" },
+    current = { link = "QuickFixLine" },
+    git_modified = { link = "DiffChange" },
+    git_staged = { link = "DiffAdd" },
+    git_untracked = { link = "Special" },
+    git_ignored = { link = "Comment" },
+    git_conflicted = { link = "DiffDelete" },
+    git_dirty = { link = "DiffChange" },
+    placeholder = { link = "Comment" },
 }
 
 local hl_names = {
@@ -26,6 +35,15 @@ local hl_names = {
     other_file = "LazyFilerOther",
     link_to = "LazyFilerLinkTo",
     indent = "LazyFilerIndent",
+    selected = "LazyFilerSelected",
+    current = "LazyFilerCurrent",
+    git_modified = "LazyFilerGitModified",
+    git_staged = "LazyFilerGitStaged",
+    git_untracked = "LazyFilerGitUntracked",
+    git_ignored = "LazyFilerGitIgnored",
+    git_conflicted = "LazyFilerGitConflicted",
+    git_dirty = "LazyFilerGitDirty",
+    placeholder = "LazyFilerPlaceholder",
 }
 
 function M.set_highlight_groups(opts)
@@ -59,6 +77,20 @@ for key, hl in pairs(hl_names) do
     end
 end
 
+M.set_extmark.selected = function(buf, opts)
+    api.nvim_buf_set_extmark(buf, ns, opts.line, 0, {
+        line_hl_group = hl_names.selected,
+        invalidate = true,
+    })
+end
+
+M.set_extmark.current = function(buf, opts)
+    api.nvim_buf_set_extmark(buf, ns, opts.line, 0, {
+        line_hl_group = hl_names.current,
+        invalidate = true,
+    })
+end
+
 M.set_extmark.empty_line = function(buf, opts)
     api.nvim_buf_set_extmark(buf, ns, opts.line, 0, {
         virt_lines = { { { "", "Comment" } } },
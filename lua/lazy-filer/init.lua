@@ -50,6 +50,16 @@ M.fn = {
         states.dir_displayed.set(parent)
     end,
 
+    history_back = function()
+        local root = rpc_call.history_back()
+        if root then states.dir_displayed.set(root) end
+    end,
+
+    history_forward = function()
+        local root = rpc_call.history_forward()
+        if root then states.dir_displayed.set(root) end
+    end,
+
     new_filer = function()
         local cwd = vim.uv.cwd()
         states.dir_displayed.set(cwd)
@@ -66,6 +76,30 @@ M.fn = {
         rpc_call.open_or_expand(line_idx)
     end,
 
+    open_split = function()
+        local line_idx = get_line_idx()
+        rpc_call.open_or_expand(line_idx, "split")
+    end,
+
+    open_vsplit = function()
+        local line_idx = get_line_idx()
+        rpc_call.open_or_expand(line_idx, "vsplit")
+    end,
+
+    open_tab = function()
+        local line_idx = get_line_idx()
+        rpc_call.open_or_expand(line_idx, "tab")
+    end,
+
+    open_in_window = function()
+        local line_idx = get_line_idx()
+        vim.ui.input({ prompt = "Window number: " }, function(nr)
+            local win = nr and vim.fn.win_getid(tonumber(nr) or 0) or 0
+            if win == 0 then return end
+            rpc_call.open_or_expand(line_idx, "window", win)
+        end)
+    end,
+
     refresh = function()
         local cwd = states.dir_displayed.get()
         if cwd then
@@ -77,11 +111,217 @@ M.fn = {
         end
     end,
 
+    yank_entry = function()
+        local line_idx = get_line_idx()
+        rpc_call.yank_entry(line_idx)
+    end,
+
+    cut_entry = function()
+        local line_idx = get_line_idx()
+        rpc_call.cut_entry(line_idx)
+    end,
+
+    paste_entry = function()
+        local line_idx = get_line_idx()
+        rpc_call.paste_entry(line_idx)
+    end,
+
+    link_entry = function()
+        local line_idx = get_line_idx()
+        if not line_idx then return end
+        vim.ui.input({ prompt = "Link name (empty for the target's name): " }, function(fname)
+            if not fname then return end
+            rpc_call.link_entry(line_idx, false, fname)
+        end)
+    end,
+
+    hardlink_entry = function()
+        local line_idx = get_line_idx()
+        if not line_idx then return end
+        vim.ui.input({ prompt = "Hard link name (empty for the target's name): " }, function(fname)
+            if not fname then return end
+            rpc_call.link_entry(line_idx, true, fname)
+        end)
+    end,
+
+    toggle_hidden = function()
+        local cwd = states.dir_displayed.get()
+        if not cwd then return end
+        rpc_call.toggle_hidden(cwd)
+    end,
+
+    toggle_ignored = function()
+        local cwd = states.dir_displayed.get()
+        if not cwd then return end
+        rpc_call.toggle_ignored(cwd)
+    end,
+
+    search = function()
+        local cwd = states.dir_displayed.get()
+        if not cwd then return end
+
+        vim.ui.input({ prompt = "Search: " }, function(query)
+            if not query or query == "" then return end
+
+            local matches = rpc_call.search(cwd, query, states.opts.search_depth)
+            if #matches == 0 then
+                vim.notify("lazy-filer: no match for " .. query, vim.log.levels.INFO)
+                return
+            end
+
+            vim.ui.select(matches, {
+                prompt = "Reveal: ",
+                format_item = function(item)
+                    return string.sub(item.path, string.len(cwd) + 2)
+                end,
+            }, function(item)
+                if not item then return end
+                rpc_call.reveal_match(cwd, item.path)
+            end)
+        end)
+    end,
+
+    reveal_current_file = function()
+        local path = api.nvim_buf_get_name(0)
+        if path == "" or vim.bo.buftype ~= "" then return end
+
+        local cwd = states.dir_displayed.get() or vim.uv.cwd()
+        local revealed = rpc_call.reveal_path(cwd, path)
+        if not revealed then return end
+        states.dir_displayed.set(revealed.root)
+    end,
+
+    add_bookmark = function()
+        local line_idx = get_line_idx()
+        if not line_idx then return end
+        vim.ui.input({ prompt = "Bookmark name (empty for the directory name): " }, function(name)
+            if not name then return end
+            rpc_call.add_bookmark(line_idx, name)
+        end)
+    end,
+
+    open_bookmark = function()
+        local bookmarks = rpc_call.list_bookmarks()
+        if #bookmarks == 0 then
+            vim.notify("lazy-filer: no bookmarks", vim.log.levels.INFO)
+            return
+        end
+
+        vim.ui.select(bookmarks, {
+            prompt = "Open bookmark: ",
+            format_item = function(item) return item.name .. "  " .. item.path end,
+        }, function(item)
+            if not item then return end
+            states.dir_displayed.set(item.path)
+            rpc_call.new_filer(item.path)
+        end)
+    end,
+
+    delete_bookmark = function()
+        local bookmarks = rpc_call.list_bookmarks()
+        if #bookmarks == 0 then return end
+
+        vim.ui.select(bookmarks, {
+            prompt = "Delete bookmark: ",
+            format_item = function(item) return item.name .. "  " .. item.path end,
+        }, function(item)
+            if not item then return end
+            rpc_call.delete_bookmark(item.name)
+        end)
+    end,
+
+    select_sort = function()
+        local cwd = states.dir_displayed.get()
+        if not cwd then return end
+
+        local keys = { "name", "natural", "case_insensitive", "extension", "size", "mtime" }
+        vim.ui.select(keys, { prompt = "Sort by: " }, function(key)
+            if not key then return end
+            local sort = states.sort.get() or vim.deepcopy(states.opts.sort)
+            sort.by = key
+            states.sort.set(sort)
+            rpc_call.sort_by(cwd)
+        end)
+    end,
+
+    toggle_dirs_first = function()
+        local cwd = states.dir_displayed.get()
+        if not cwd then return end
+        local sort = states.sort.get() or vim.deepcopy(states.opts.sort)
+        sort.dirs_first = not sort.dirs_first
+        states.sort.set(sort)
+        rpc_call.sort_by(cwd)
+    end,
+
+    toggle_sort_reverse = function()
+        local cwd = states.dir_displayed.get()
+        if not cwd then return end
+        local sort = states.sort.get() or vim.deepcopy(states.opts.sort)
+        sort.reverse = not sort.reverse
+        states.sort.set(sort)
+        rpc_call.sort_by(cwd)
+    end,
+
+    toggle_selection = function()
+        local line_idx = get_line_idx()
+        rpc_call.toggle_selection(line_idx)
+    end,
+
+    select_range = function()
+        local start_line_idx = vim.fn.line("v")
+        local end_line_idx = vim.fn.line(".")
+        api.nvim_feedkeys(api.nvim_replace_termcodes("<ESC>", true, false, true), "nx", false)
+        rpc_call.select_range(start_line_idx, end_line_idx)
+    end,
+
+    select_by_glob = function()
+        vim.ui.input({ prompt = "Select by glob: " }, function(pattern)
+            if not pattern or pattern == "" then return end
+            rpc_call.select_glob(pattern)
+        end)
+    end,
+
+    clear_selection = function()
+        rpc_call.clear_selection()
+    end,
+
+    chmod_entry = function()
+        local line_idx = get_line_idx()
+        if not line_idx then return end
+        vim.ui.input({ prompt = "Mode: " }, function(mode)
+            if not mode or mode == "" then return end
+            rpc_call.chmod_entry(line_idx, mode, false)
+        end)
+    end,
+
+    chmod_entry_recursive = function()
+        local line_idx = get_line_idx()
+        if not line_idx then return end
+        vim.ui.input({ prompt = "Mode (recursive): " }, function(mode)
+            if not mode or mode == "" then return end
+            rpc_call.chmod_entry(line_idx, mode, true)
+        end)
+    end,
+
+    undo = function()
+        rpc_call.undo()
+    end,
+
+    redo = function()
+        rpc_call.redo()
+    end,
+
+    toggle_preview = subwin.preview.toggle,
+
     create_entry = subwin.create_entry.exec,
     open_new_entry_win = subwin.create_entry.open_win,
     open_delete_entry_win = subwin.delete_entry.open_win,
     open_rename_entry_win = subwin.rename_entry.open_win,
     rename_entry = subwin.rename_entry.exec,
+    open_trash_win = subwin.trash.open_win,
+    open_bulk_rename_win = subwin.bulk_rename.open_win,
+    bulk_rename = subwin.bulk_rename.exec,
+    restore_entry = subwin.trash.exec,
 
     move_to_filer = function()
         ui.main.focus()
@@ -143,9 +383,62 @@ function M.setup(opts)
                     end
                 end
             end,
+
+            trash = function(buf)
+                if opts.keymaps.trash then
+                    for _, args in ipairs(opts.keymaps.trash) do
+                        define_keymaps_wrap(args, { buffer = buf, silent = true })
+                    end
+                end
+            end,
+
+            bulk_rename = function(buf)
+                if opts.keymaps.bulk_rename then
+                    for _, args in ipairs(opts.keymaps.bulk_rename) do
+                        define_keymaps_wrap(args, { buffer = buf, silent = true })
+                    end
+                end
+            end,
         })
     end
 
+    if opts.use_trash ~= nil then
+        states.opts.use_trash = opts.use_trash
+    end
+    if opts.hide_hidden ~= nil then
+        states.opts.hide_hidden = opts.hide_hidden
+    end
+    if opts.hide_ignored ~= nil then
+        states.opts.hide_ignored = opts.hide_ignored
+    end
+    if opts.search_depth then
+        states.opts.search_depth = opts.search_depth
+    end
+    if opts.max_entries then
+        states.opts.max_entries = opts.max_entries
+    end
+    if opts.columns then
+        states.opts.columns = opts.columns
+    end
+    if opts.time_format then
+        states.opts.time_format = opts.time_format
+    end
+    if opts.follow_current_file ~= nil then
+        states.opts.follow_current_file = opts.follow_current_file
+    end
+    if opts.preview then
+        states.opts.preview = vim.tbl_extend("force", states.opts.preview, opts.preview)
+    end
+    if opts.delete_buffers ~= nil then
+        states.opts.delete_buffers = opts.delete_buffers
+    end
+    if opts.lsp then
+        states.opts.lsp = vim.tbl_extend("force", states.opts.lsp, opts.lsp)
+    end
+    if opts.sort then
+        states.opts.sort = vim.tbl_extend("force", states.opts.sort, opts.sort)
+    end
+
     if opts.border then
         ui.update_opts({ background = opts.border })
     end
@@ -153,6 +446,55 @@ function M.setup(opts)
     hl.set_highlight_groups(opts.hl)
 
     ns.register(opts.plugin_dir, opts.rpc_ns)
+
+    -- Writing a buffer does not touch its directory, so the watcher never sees it.
+    api.nvim_create_autocmd("BufWritePost", {
+        group = api.nvim_create_augroup("NaughieLazyFilerGit", { clear = true }),
+        callback = function(ev)
+            if not states.dir_displayed.get() then return end
+            local path = api.nvim_buf_get_name(ev.buf)
+            if path == "" then return end
+            rpc_call.update_git_status(vim.fs.dirname(path))
+        end,
+    })
+
+    api.nvim_create_autocmd("BufEnter", {
+        group = api.nvim_create_augroup("NaughieLazyFilerFollow", { clear = true }),
+        callback = function(ev)
+            if not states.opts.follow_current_file then return end
+            if vim.bo[ev.buf].buftype ~= "" then return end
+            local path = api.nvim_buf_get_name(ev.buf)
+            if path == "" then return end
+            local cwd = states.dir_displayed.get()
+            if not cwd then return end
+            rpc_call.follow_path(cwd, path)
+        end,
+    })
+
+    local instance_group = api.nvim_create_augroup("NaughieLazyFilerInstance", { clear = true })
+    api.nvim_create_autocmd("TabEnter", {
+        group = instance_group,
+        callback = function()
+            local instance = states.instances[api.nvim_get_current_tabpage()]
+            if not instance or not instance.stale then return end
+            instance.stale = false
+            rpc_call.redraw()
+        end,
+    })
+    api.nvim_create_autocmd("TabClosed", {
+        group = instance_group,
+        callback = function()
+            rpc_call.dispose_closed()
+        end,
+    })
+
+    -- A request, not a notification, so that Neovim waits for the state file to be written.
+    api.nvim_create_autocmd("VimLeavePre", {
+        group = api.nvim_create_augroup("NaughieLazyFilerSession", { clear = true }),
+        callback = function()
+            rpc_call.save_session()
+        end,
+    })
 end
 
 return M
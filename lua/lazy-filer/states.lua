@@ -4,8 +4,31 @@ local myui = require("my-ui")
 local states = {
     tmp_create_entry_states = { dir = nil },
     tmp_rename_entry_states = { file = nil, cwd = nil },
+    tmp_trash_states = { entries = nil },
+    tmp_bulk_rename_states = { dir = nil, names = nil },
+    tmp_preview_states = { buf = nil, autocmd = nil, timer = nil, line = nil },
+
+    opts = {
+        use_trash = false,
+        hide_hidden = false,
+        hide_ignored = false,
+        sort = { by = "name", dirs_first = false, reverse = false },
+        search_depth = 4,
+        max_entries = 1000,
+        columns = {},
+        time_format = nil,
+        follow_current_file = false,
+        preview = { max_lines = 100, max_bytes = 262144, delay = 50 },
+        delete_buffers = nil,
+        lsp = { file_operations = true, timeout = 1000 },
+    },
 
     dir_displayed = mkstate.tab(),
+    sort = mkstate.tab(),
+
+    -- Filer instances by tabpage handle. A hidden instance is stale when its buffer has
+    -- missed updates, and is redrawn when its tabpage is entered.
+    instances = {},
 
     ui = myui.declare_ui({ main = { hide_cursor = true } }),
 }
@@ -12,11 +12,15 @@ local augroup = api.nvim_create_augroup("NaughieLazyFilerSubwin", { clear = true
 local companion_keymaps = {
     new_entry = nil,
     rename_entry = nil,
+    trash = nil,
+    bulk_rename = nil,
 }
 
 function M.set_keymaps(keymaps)
     companion_keymaps.new_entry = keymaps.new_entry
     companion_keymaps.rename_entry = keymaps.rename_entry
+    companion_keymaps.trash = keymaps.trash
+    companion_keymaps.bulk_rename = keymaps.bulk_rename
 end
 
 local function get_line_idx()
@@ -35,6 +39,7 @@ M.create_entry = {
         if not dir.name then return end
         states.tmp_create_entry_states.dir = dir
 
+        M.preview.close()
         if not ui.companion.get_buf() then
             ui.companion.create_buf(function(buf)
                 if companion_keymaps.new_entry then
@@ -90,6 +95,7 @@ M.delete_entry = {
         local file = rpc_call.get_file_path(line_idx)
         if not file.name then return end
 
+        M.preview.close()
         if not ui.companion.get_buf() then
             local close = function()
                 ui.main.focus()
@@ -97,7 +103,7 @@ M.delete_entry = {
             end
             local confirm = function()
                 close()
-                rpc_call.delete_entry(file.idx)
+                rpc_call.delete_entry(file.idx, states.opts.use_trash)
             end
 
             ui.companion.create_buf(function(buf)
@@ -108,10 +114,19 @@ M.delete_entry = {
             end)
         end
 
+        local action = "Delete an entry: "
+        if states.opts.use_trash then action = "Move to trash: " end
+
+        local target = file.name
+        local selection = rpc_call.get_selection()
+        if #selection > 0 then
+            target = #selection .. " selected entries"
+        end
+
         local prompt = {
             "",
             "",
-            "    Delete an entry: " .. file.name,
+            "    " .. action .. target,
             "       Are you sure? [y/N]",
             "",
             "",
@@ -153,6 +168,7 @@ M.rename_entry = {
         if not file.name then return end
         states.tmp_rename_entry_states.file = file
 
+        M.preview.close()
         if not ui.companion.get_buf() then
             ui.companion.create_buf(function(buf)
                 if companion_keymaps.rename_entry then
@@ -161,8 +177,14 @@ M.rename_entry = {
             end)
         end
 
+        local title = "Rename an entry: " .. file.name
+        local selection = rpc_call.get_selection()
+        if #selection > 0 then
+            title = "Move " .. #selection .. " selected entries into: "
+        end
+
         ui.companion.set_lines(0, -1, false, {
-            "Rename an entry: " .. file.name,
+            title,
             "",
         })
 
@@ -207,4 +229,247 @@ M.rename_entry = {
     end,
 }
 
+M.bulk_rename = {
+    open_win = function()
+        local line_idx = get_line_idx()
+
+        local targets = rpc_call.get_rename_targets(line_idx)
+        if not targets or #targets.names == 0 then return end
+        states.tmp_bulk_rename_states = { dir = targets.dir, names = targets.names }
+
+        M.preview.close()
+        if not ui.companion.get_buf() then
+            ui.companion.create_buf(function(buf)
+                api.nvim_set_option_value("buftype", "acwrite", { buf = buf })
+                api.nvim_buf_set_name(buf, "lazy-filer://bulk-rename/" .. targets.dir)
+                api.nvim_create_autocmd("BufWriteCmd", {
+                    group = augroup,
+                    buffer = buf,
+                    callback = function()
+                        M.bulk_rename.exec()
+                    end,
+                })
+
+                if companion_keymaps.bulk_rename then
+                    companion_keymaps.bulk_rename(buf)
+                end
+            end)
+        end
+
+        ui.companion.set_lines(0, -1, false, targets.names)
+
+        ui.companion.open_float(function(win)
+            api.nvim_create_autocmd("WinClosed", {
+                group = augroup,
+                pattern = tostring(win),
+                callback = function()
+                    ui.companion.delete_buf()
+                    states.tmp_bulk_rename_states = { dir = nil, names = nil }
+                end,
+            })
+        end)
+    end,
+
+    exec = function()
+        vim.cmd("stopinsert")
+        local dir = states.tmp_bulk_rename_states.dir
+        local names = states.tmp_bulk_rename_states.names
+        if not dir or not names then return end
+
+        local lines = ui.companion.lines(0, -1, false)
+        if #lines ~= #names then
+            vim.notify(
+                string.format("lazy-filer: expected %d lines, found %d", #names, #lines),
+                vim.log.levels.WARN
+            )
+            return
+        end
+
+        -- Keep the buffer open on failure so that the names can be fixed and written again.
+        if not rpc_call.bulk_rename(dir, names, lines) then return end
+
+        local buf = ui.companion.get_buf()
+        if buf then api.nvim_set_option_value("modified", false, { buf = buf }) end
+
+        ui.main.focus()
+        ui.companion.close()
+    end,
+}
+
+M.trash = {
+    open_win = function()
+        local cwd = states.dir_displayed.get() or vim.uv.cwd()
+        local entries = rpc_call.list_trash(cwd)
+        if #entries == 0 then
+            vim.notify("lazy-filer: the trash is empty", vim.log.levels.INFO)
+            return
+        end
+        states.tmp_trash_states.entries = entries
+
+        M.preview.close()
+        if not ui.companion.get_buf() then
+            ui.companion.create_buf(function() end)
+        end
+        -- Installed on every listing, as the buffer may have been made for another subwindow.
+        local buf = ui.companion.get_buf()
+        if buf and companion_keymaps.trash then
+            companion_keymaps.trash(buf)
+        end
+
+        local lines = { "Trashed entries:" }
+        for _, entry in ipairs(entries) do
+            table.insert(lines, entry.deleted_at .. "  " .. entry.path)
+        end
+        ui.companion.set_lines(0, -1, false, lines)
+
+        ui.companion.open_float(function(win)
+            api.nvim_create_autocmd("WinClosed", {
+                group = augroup,
+                pattern = tostring(win),
+                callback = function()
+                    ui.companion.delete_buf()
+                    states.tmp_trash_states.entries = nil
+                end,
+            })
+        end)
+
+        local win = ui.companion.get_win()
+        if not win then return end
+        api.nvim_win_set_cursor(win, { 2, 0 })
+    end,
+
+    exec = function()
+        local entries = states.tmp_trash_states.entries
+        if not entries then return end
+
+        local win = ui.companion.get_win()
+        if not win then return end
+
+        local cursor = api.nvim_win_get_cursor(win)
+        local entry = entries[cursor[1] - 1]
+        if not entry then return end
+
+        ui.main.focus()
+        ui.companion.close()
+
+        rpc_call.restore_entry(entry.id)
+    end,
+}
+
+local function preview_lines(preview)
+    if preview.kind == "text" then
+        local lines = preview.lines
+        if preview.truncated then table.insert(lines, "…") end
+        return lines, vim.filetype.match({ filename = preview.path })
+    elseif preview.kind == "binary" then
+        return { "Binary file, " .. preview.size_human }
+    elseif preview.kind == "directory" then
+        local lines = {
+            string.format(
+                "%d directories, %d files, %d links, %d others, %s in total",
+                preview.dirs, preview.files, preview.links, preview.others, preview.total_size_human
+            ),
+            "",
+        }
+        vim.list_extend(lines, preview.names)
+        if preview.truncated then table.insert(lines, "…") end
+        return lines
+    elseif preview.kind == "link" then
+        local head = "-> " .. preview.to
+        if preview.broken then return { head .. " (broken)" } end
+
+        local target = preview.target
+        target.path = preview.path
+        local lines, filetype = preview_lines(target)
+        table.insert(lines, 1, head)
+        table.insert(lines, 2, "")
+        return lines, filetype
+    elseif preview.kind == "other" then
+        return { preview.mode_str }
+    elseif preview.kind == "timed_out" then
+        return { "Timed out" }
+    elseif preview.kind == "error" then
+        return { preview.message }
+    end
+
+    return {}
+end
+
+-- The preview follows the cursor of the filer window until the subwindow is closed, or until
+-- another subwindow replaces it.
+M.preview = {
+    open_win = function()
+        local main_buf = ui.main.get_buf()
+        if not main_buf or states.tmp_preview_states.buf then return end
+
+        if not ui.companion.get_buf() then
+            ui.companion.create_buf(function() end)
+        end
+        states.tmp_preview_states.buf = ui.companion.get_buf()
+        M.preview.update()
+
+        ui.companion.open_float(function(win)
+            api.nvim_create_autocmd("WinClosed", {
+                group = augroup,
+                pattern = tostring(win),
+                callback = function()
+                    ui.companion.delete_buf()
+
+                    local tmp = states.tmp_preview_states
+                    if tmp.autocmd then api.nvim_del_autocmd(tmp.autocmd) end
+                    if tmp.timer then tmp.timer:close() end
+                    states.tmp_preview_states = { buf = nil, autocmd = nil, timer = nil, line = nil }
+                end,
+            })
+        end)
+        ui.main.focus()
+
+        local timer = vim.uv.new_timer()
+        states.tmp_preview_states.timer = timer
+        states.tmp_preview_states.autocmd = api.nvim_create_autocmd("CursorMoved", {
+            group = augroup,
+            buffer = main_buf,
+            callback = function()
+                timer:stop()
+                timer:start(states.opts.preview.delay, 0, vim.schedule_wrap(M.preview.update))
+            end,
+        })
+    end,
+
+    update = function()
+        local buf = states.tmp_preview_states.buf
+        if not buf or buf ~= ui.companion.get_buf() then return end
+
+        local line_idx = get_line_idx()
+        if not line_idx then return end
+
+        states.tmp_preview_states.line = line_idx
+        rpc_call.preview(line_idx)
+    end,
+
+    -- Previews of a line other than the one asked for last, or than the cursor line, are stale.
+    show = function(line_idx, preview)
+        local buf = states.tmp_preview_states.buf
+        if not buf or buf ~= ui.companion.get_buf() then return end
+        if line_idx ~= states.tmp_preview_states.line or line_idx ~= get_line_idx() then return end
+
+        local lines, filetype = preview_lines(preview)
+        ui.companion.set_lines(0, -1, false, lines)
+        api.nvim_set_option_value("filetype", filetype or "", { buf = buf })
+    end,
+
+    close = function()
+        if not states.tmp_preview_states.buf then return end
+        ui.companion.close()
+    end,
+
+    toggle = function()
+        if states.tmp_preview_states.buf then
+            M.preview.close()
+        else
+            M.preview.open_win()
+        end
+    end,
+}
+
 return M